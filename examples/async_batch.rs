@@ -0,0 +1,35 @@
+use std::net::Ipv4Addr;
+
+use tidy_tuntap::*;
+use tokio::net::UdpSocket;
+
+#[tokio::main]
+async fn main() {
+    let tun = AsyncTun::new("tun10", false).unwrap();
+    tun.bring_up().unwrap();
+    tun.set_addr(Ipv4Addr::new(10, 10, 10, 1)).unwrap();
+    tun.set_netmask(Ipv4Addr::new(255, 255, 255, 0)).unwrap();
+
+    let udp_socket = UdpSocket::bind("10.10.10.1:33333").await.unwrap();
+    for i in 0..4 {
+        udp_socket
+            .send_to(&[i; 10], "10.10.10.2:44444")
+            .await
+            .unwrap();
+    }
+
+    let mut bufs = vec![PacketBuf::new(1500); 8];
+    let mut received = 0;
+    while received < 4 {
+        let n = tun.recv_batch(&mut bufs).await.unwrap();
+
+        received += bufs[..n]
+            .iter()
+            .filter(|packet| packet[0] >> 4 == 4 && packet[9] == 17)
+            .count();
+    }
+
+    // Nothing is left to be read.
+    let err = tun.try_recv_batch(&mut bufs).unwrap_err();
+    assert_eq!(err.errno(), Some(nix::errno::Errno::EAGAIN));
+}
@@ -0,0 +1,22 @@
+use futures::StreamExt;
+use tidy_tuntap::events::Event;
+use tidy_tuntap::*;
+
+#[tokio::main]
+async fn main() {
+    let tun = AsyncTun::new("tun10", false).unwrap();
+    let mut events = tun.events().unwrap();
+
+    tun.bring_up().unwrap();
+    tun.set_mtu(1280).unwrap();
+
+    let mut mtu_changed = false;
+    let mut up = false;
+    while !(mtu_changed && up) {
+        match events.next().await.unwrap().unwrap() {
+            Event::Up => up = true,
+            Event::MtuChanged(1280) => mtu_changed = true,
+            _ => {}
+        }
+    }
+}
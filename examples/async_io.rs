@@ -0,0 +1,53 @@
+use std::net::{Ipv4Addr, UdpSocket};
+
+use futures::{AsyncReadExt, AsyncWriteExt};
+use tidy_tuntap::async_io::AsyncTun;
+
+fn main() {
+    async_io::block_on(async {
+        let mut tun = AsyncTun::new("tun10", false).unwrap();
+        tun.bring_up().unwrap();
+        tun.set_addr(Ipv4Addr::new(10, 10, 10, 1)).unwrap();
+        tun.set_netmask(Ipv4Addr::new(255, 255, 255, 0)).unwrap();
+
+        let udp_socket = UdpSocket::bind("10.10.10.1:33333").unwrap();
+        udp_socket.send_to(&[1; 10], "10.10.10.2:44444").unwrap();
+
+        // Read through `futures::AsyncRead` until our packet shows up.
+        let mut buf = [0; 1500];
+        loop {
+            let n = tun.read(&mut buf).await.unwrap();
+
+            if n == 38 && buf[9] == 17 {
+                break;
+            }
+        }
+        assert_eq!(buf[28..38], [1; 10]);
+
+        // Echo it back by swapping its addresses and ports, which keeps the checksums valid.
+        let (src, dst) = buf[12..20].split_at_mut(4);
+        src.swap_with_slice(dst);
+        let (src_port, dst_port) = buf[20..24].split_at_mut(2);
+        src_port.swap_with_slice(dst_port);
+        tun.write_all(&buf[..38]).await.unwrap();
+
+        let mut payload = [0; 100];
+        let (n, _) = udp_socket.recv_from(&mut payload).unwrap();
+        assert_eq!(payload[..n], [1; 10]);
+
+        // The inherent methods work the same way.
+        udp_socket.send_to(&[2; 10], "10.10.10.2:44444").unwrap();
+        loop {
+            let n = tun.recv(&mut buf).await.unwrap();
+
+            if n == 38 && buf[9] == 17 {
+                assert_eq!(buf[28..38], [2; 10]);
+                break;
+            }
+        }
+
+        // Errors keep the failed operation.
+        let err = tun.send(&[]).await.unwrap_err();
+        assert_eq!(err.op(), Some("write"));
+    });
+}
@@ -0,0 +1,94 @@
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+
+use tidy_tuntap::*;
+use tokio::net::UdpSocket;
+
+#[tokio::main(flavor = "multi_thread", worker_threads = 3)]
+async fn main() {
+    let queues: Vec<_> = AsyncMQTun::new("tun10", 3, false)
+        .unwrap()
+        .into_iter()
+        .map(Arc::new)
+        .collect();
+
+    queues[0].bring_up().unwrap();
+    queues[0].set_addr(Ipv4Addr::new(10, 10, 10, 1)).unwrap();
+    queues[0]
+        .set_netmask(Ipv4Addr::new(255, 255, 255, 0))
+        .unwrap();
+
+    // One task per queue, each echoing the UDP packets sent to 10.10.10.2:44444 back by
+    // swapping their addresses and ports, which keeps the checksums valid.
+    for queue in &queues {
+        let queue = queue.clone();
+
+        tokio::spawn(async move {
+            let mut buf = [0; 1500];
+            loop {
+                let n = queue.recv(&mut buf).await.unwrap();
+                let ihl = (buf[0] & 0xf) as usize * 4;
+
+                if buf[0] >> 4 != 4
+                    || buf[9] != 17
+                    || buf[ihl + 2..ihl + 4] != 44444u16.to_be_bytes()
+                {
+                    continue;
+                }
+
+                let (src, dst) = buf[12..20].split_at_mut(4);
+                src.swap_with_slice(dst);
+                let (src_port, dst_port) = buf[ihl..ihl + 4].split_at_mut(2);
+                src_port.swap_with_slice(dst_port);
+
+                queue.send(&buf[..n]).await.unwrap();
+            }
+        });
+    }
+
+    // Flows from different ports are spread over the queues.
+    let echo = |port: u16| async move {
+        let udp_socket = UdpSocket::bind(("10.10.10.1", port)).await.unwrap();
+        udp_socket
+            .send_to(&port.to_be_bytes(), "10.10.10.2:44444")
+            .await
+            .unwrap();
+
+        let mut buf = [0; 100];
+        let (n, from) = udp_socket.recv_from(&mut buf).await.unwrap();
+        assert_eq!(buf[..n], port.to_be_bytes());
+        assert_eq!(from, "10.10.10.2:44444".parse().unwrap());
+    };
+
+    let flows: Vec<_> = (33331..33337)
+        .map(|port| tokio::spawn(echo(port)))
+        .collect();
+    for flow in flows {
+        flow.await.unwrap();
+    }
+
+    // The packets of a detached queue are steered to the remaining ones.
+    queues[1].detach().unwrap();
+    queues[2].detach().unwrap();
+    assert_eq!(queues[0].queue_index(), Some(0));
+    assert_eq!(queues[2].queue_index(), None);
+
+    let flows: Vec<_> = (33341..33344)
+        .map(|port| tokio::spawn(echo(port)))
+        .collect();
+    for flow in flows {
+        flow.await.unwrap();
+    }
+
+    queues[2].attach().unwrap();
+    queues[1].attach().unwrap();
+    assert_eq!(queues[2].queue_index(), Some(1));
+    assert_eq!(queues[1].queue_index(), Some(2));
+
+    let flows: Vec<_> = (33351..33354)
+        .map(|port| tokio::spawn(echo(port)))
+        .collect();
+    for flow in flows {
+        flow.await.unwrap();
+    }
+}
@@ -0,0 +1,139 @@
+use std::net::Ipv4Addr;
+
+use futures::{SinkExt, StreamExt, TryStreamExt};
+use tidy_tuntap::*;
+use tokio::net::UdpSocket;
+
+// Returns whether `packet` is a UDP over IPv4 packet sent to port 44444.
+fn is_echo_request(packet: &PacketBuf) -> bool {
+    let ihl = (packet[0] & 0xf) as usize * 4;
+
+    packet[0] >> 4 == 4 && packet[9] == 17 && packet[ihl + 2..ihl + 4] == 44444u16.to_be_bytes()
+}
+
+// Echoes the packet back by swapping its addresses and ports, which keeps the checksums valid.
+fn into_echo_reply(mut packet: PooledBuf) -> PooledBuf {
+    let ihl = (packet[0] & 0xf) as usize * 4;
+    let (src, dst) = packet[12..20].split_at_mut(4);
+    src.swap_with_slice(dst);
+    let (src_port, dst_port) = packet[ihl..ihl + 4].split_at_mut(2);
+    src_port.swap_with_slice(dst_port);
+
+    packet
+}
+
+async fn echo(port: u16) {
+    let udp_socket = UdpSocket::bind(("10.10.10.1", port)).await.unwrap();
+    udp_socket
+        .send_to(&port.to_be_bytes(), "10.10.10.2:44444")
+        .await
+        .unwrap();
+
+    let mut buf = [0; 100];
+    let (n, from) = udp_socket.recv_from(&mut buf).await.unwrap();
+    assert_eq!(buf[..n], port.to_be_bytes());
+    assert_eq!(from, "10.10.10.2:44444".parse().unwrap());
+}
+
+#[tokio::main]
+async fn main() {
+    let tun = AsyncTun::new("tun10", false).unwrap();
+    tun.bring_up().unwrap();
+    tun.set_addr(Ipv4Addr::new(10, 10, 10, 1)).unwrap();
+    tun.set_netmask(Ipv4Addr::new(255, 255, 255, 0)).unwrap();
+
+    // The buffers are sized from the MTU.
+    let packets = tun.into_packets().unwrap();
+    assert_eq!(packets.buffer_size(), 1500);
+
+    // The packets of the device are forwarded back to it through stream combinators.
+    let (sink, stream) = packets.split();
+    let pipeline = tokio::spawn(
+        stream
+            .try_filter(|packet| futures::future::ready(is_echo_request(packet)))
+            .map_ok(into_echo_reply)
+            .take(2)
+            .forward(sink),
+    );
+
+    echo(33333).await;
+    echo(33334).await;
+    pipeline.await.unwrap().unwrap();
+
+    // The split halves are adapted the same way.
+    let tun = AsyncTun::new("tun11", false).unwrap();
+    tun.bring_up().unwrap();
+    tun.set_addr(Ipv4Addr::new(10, 10, 11, 1)).unwrap();
+    tun.set_netmask(Ipv4Addr::new(255, 255, 255, 0)).unwrap();
+
+    let (reader, writer) = tun.into_split();
+    let mut stream = Packets::with_buffer_size(reader, 28);
+    let mut sink = writer.into_packets().unwrap();
+
+    let udp_socket = UdpSocket::bind("10.10.11.1:33333").await.unwrap();
+    udp_socket
+        .send_to(&[1; 100], "10.10.11.2:44444")
+        .await
+        .unwrap();
+
+    // A packet which doesn't fit fails without ending the stream.
+    loop {
+        match stream.next().await.unwrap() {
+            Err(error::Error::PacketTruncated(28)) => break,
+            Err(err) => panic!("{err}"),
+            Ok(_) => {}
+        }
+    }
+
+    stream.set_buffer_size(1500);
+    udp_socket
+        .send_to(&[2; 10], "10.10.11.2:44444")
+        .await
+        .unwrap();
+
+    // Skips the truncated packet, if the stream failed on another one.
+    let packet = loop {
+        let packet = stream.next().await.unwrap().unwrap();
+        if is_echo_request(&packet) && packet.len() == 38 {
+            break packet;
+        }
+    };
+
+    sink.send(into_echo_reply(packet)).await.unwrap();
+
+    let mut buf = [0; 100];
+    let (n, from) = udp_socket.recv_from(&mut buf).await.unwrap();
+    assert_eq!(buf[..n], [2; 10]);
+    assert_eq!(from, "10.10.11.2:44444".parse().unwrap());
+
+    // The buffers of the packets go back to the pool once they're dropped.
+    let pool = BufferPool::new(1500, 0, 0);
+    let tun = AsyncTun::new("tun12", false).unwrap();
+    tun.bring_up().unwrap();
+    let mut stream = Packets::with_pool(tun, pool.clone());
+
+    // Bringing the interface up makes the kernel send some packets, e.g. router solicitations.
+    let packet = stream.next().await.unwrap().unwrap();
+    assert!(!packet.is_empty());
+    assert_eq!(pool.idle(), 0);
+    drop(packet);
+    assert_eq!(pool.idle(), 1);
+
+    // A read failing for another reason ends the stream, e.g. when the buffers can't hold the
+    // packet info.
+    let tun = AsyncTun::new("tun13", true).unwrap();
+    tun.bring_up().unwrap();
+    tun.set_addr(Ipv4Addr::new(10, 10, 13, 1)).unwrap();
+    tun.set_netmask(Ipv4Addr::new(255, 255, 255, 0)).unwrap();
+    let mut stream = Packets::with_buffer_size(tun, 2);
+
+    let udp_socket = UdpSocket::bind("10.10.13.1:33333").await.unwrap();
+    udp_socket
+        .send_to(&[3; 10], "10.10.13.2:44444")
+        .await
+        .unwrap();
+
+    let err = stream.next().await.unwrap().unwrap_err();
+    assert_eq!(err.errno(), Some(nix::errno::Errno::EINVAL), "{err}");
+    assert!(stream.next().await.is_none());
+}
@@ -0,0 +1,50 @@
+use std::net::Ipv4Addr;
+
+use tidy_tuntap::uring::AsyncIoUring;
+use tidy_tuntap::*;
+use tokio::net::UdpSocket;
+
+#[tokio::main]
+async fn main() {
+    // The ring waits for the packets, so the device itself is blocking.
+    let tun = Tun::new("tun10", false).unwrap();
+    tun.bring_up().unwrap();
+    tun.set_addr(Ipv4Addr::new(10, 10, 10, 1)).unwrap();
+    tun.set_netmask(Ipv4Addr::new(255, 255, 255, 0)).unwrap();
+
+    let mut uring = AsyncIoUring::new(&tun, 8, 1500).unwrap();
+
+    let udp_socket = UdpSocket::bind("10.10.10.1:33333").await.unwrap();
+    for i in 0..4 {
+        udp_socket
+            .send_to(&[i; 10], "10.10.10.2:44444")
+            .await
+            .unwrap();
+    }
+
+    let mut packets = Vec::new();
+    while packets.len() < 4 {
+        let batch = uring.recv_batch().await.unwrap();
+
+        packets.extend(
+            batch
+                .filter(|packet| packet[0] >> 4 == 4 && packet[9] == 17)
+                .map(|packet| packet.to_vec()),
+        );
+    }
+
+    // Echo them back by swapping their addresses and ports, which keeps the checksums valid.
+    for packet in &mut packets {
+        let (src, dst) = packet[12..20].split_at_mut(4);
+        src.swap_with_slice(dst);
+        let (src_port, dst_port) = packet[20..24].split_at_mut(2);
+        src_port.swap_with_slice(dst_port);
+    }
+    assert_eq!(uring.send_batch(&packets).await.unwrap(), 4);
+
+    let mut buf = [0; 100];
+    for i in 0..4 {
+        let (n, _) = udp_socket.recv_from(&mut buf).await.unwrap();
+        assert_eq!(buf[..n], [i; 10]);
+    }
+}
@@ -0,0 +1,78 @@
+use std::io::{IoSlice, IoSliceMut};
+use std::net::{Ipv4Addr, UdpSocket};
+
+use tidy_tuntap::*;
+
+fn main() {
+    let tun = Tun::new("tun10", false).unwrap();
+    tun.bring_up().unwrap();
+    tun.set_addr(Ipv4Addr::new(10, 10, 10, 1)).unwrap();
+    tun.set_netmask(Ipv4Addr::new(255, 255, 255, 0)).unwrap();
+
+    let udp_socket = UdpSocket::bind("10.10.10.1:33333").unwrap();
+    for i in 0..4 {
+        udp_socket.send_to(&[i; 10], "10.10.10.2:44444").unwrap();
+    }
+
+    // Keep reading until all of our packets are drained, skipping the unrelated ones.
+    let mut bufs = vec![PacketBuf::new(1500); 8];
+    let mut payloads = Vec::new();
+    while payloads.len() < 4 {
+        let n = tun.recv_batch(&mut bufs).unwrap();
+        assert!(n >= 1);
+
+        for packet in &bufs[..n] {
+            assert!(!packet.is_truncated());
+            if packet[0] >> 4 == 4 && packet[9] == 17 {
+                payloads.push(packet[packet.len() - 10..].to_vec());
+            }
+        }
+    }
+    assert_eq!(payloads, (0..4).map(|i| vec![i; 10]).collect::<Vec<_>>());
+
+    // Scatter the next packet into its IPv4 header and the rest.
+    udp_socket.send_to(&[9; 10], "10.10.10.2:44444").unwrap();
+    let (mut header, mut rest) = ([0; 20], [0; 1500]);
+    loop {
+        let n = tun
+            .recv_vectored(&mut [IoSliceMut::new(&mut header), IoSliceMut::new(&mut rest)])
+            .unwrap();
+
+        if header[0] >> 4 == 4 && header[9] == 17 {
+            assert_eq!(n, 20 + 8 + 10);
+            assert_eq!(rest[8..18], [9; 10]);
+            break;
+        }
+    }
+
+    // Echo it back by swapping its addresses and ports, which keeps the checksums valid.
+    let (src, dst) = header[12..20].split_at_mut(4);
+    src.swap_with_slice(dst);
+    let (src_port, dst_port) = rest[..4].split_at_mut(2);
+    src_port.swap_with_slice(dst_port);
+    let sent = tun
+        .send_vectored(&[IoSlice::new(&header), IoSlice::new(&rest[..18])])
+        .unwrap();
+    assert_eq!(sent, 38);
+
+    let mut buf = [0; 100];
+    let (n, from) = udp_socket.recv_from(&mut buf).unwrap();
+    assert_eq!(buf[..n], [9; 10]);
+    assert_eq!(from, "10.10.10.2:44444".parse().unwrap());
+
+    // A packet larger than the buffer is truncated, which is reported by the buffer.
+    udp_socket.send_to(&[7; 100], "10.10.10.2:44444").unwrap();
+    let mut bufs = [PacketBuf::new(50)];
+    loop {
+        let n = tun.recv_batch(&mut bufs).unwrap();
+        assert_eq!(n, 1);
+
+        let packet = &bufs[0];
+        if packet[0] >> 4 == 4 && packet[9] == 17 {
+            assert!(packet.is_truncated());
+            assert_eq!(packet.len(), 50);
+            assert_eq!(packet, &PacketBuf::from(&packet[..]));
+            break;
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::net::{Ipv4Addr, UdpSocket};
+
+use tidy_tuntap::*;
+
+fn main() {
+    // The buffers are sized from the MTU and the headers the device prepends to the packets.
+    let tun = Tun::new("tun10", false).unwrap();
+    tun.set_mtu(1400).unwrap();
+    assert_eq!(tun.buffer_size().unwrap(), 1400);
+    assert_eq!(
+        Tun::new("tun11", true).unwrap().buffer_size().unwrap(),
+        1500 + 4
+    );
+    assert_eq!(
+        Tap::new("tap10", false).unwrap().buffer_size().unwrap(),
+        1500 + 18
+    );
+
+    tun.bring_up().unwrap();
+    tun.set_addr(Ipv4Addr::new(10, 10, 10, 1)).unwrap();
+    tun.set_netmask(Ipv4Addr::new(255, 255, 255, 0)).unwrap();
+
+    let pool = BufferPool::for_device(&tun, 8, 4).unwrap();
+    assert_eq!(pool.buffer_capacity(), 1400);
+
+    let udp_socket = UdpSocket::bind("10.10.10.1:33333").unwrap();
+    udp_socket.send_to(&[1; 10], "10.10.10.2:44444").unwrap();
+
+    let mut buf = pool.get();
+    loop {
+        tun.recv_buf(&mut buf).unwrap();
+
+        if buf[0] >> 4 == 4 && buf[9] == 17 {
+            break;
+        }
+    }
+    assert_eq!(buf.len(), 20 + 8 + 10);
+    assert_eq!(buf.headroom(), 8);
+    assert_eq!(buf.tailroom(), 1400 - 38 + 4);
+
+    // Encapsulate the packet in place, then strip the headers again.
+    buf.push_front(8).copy_from_slice(&[0xAA; 8]);
+    buf.push_back(4).copy_from_slice(&[0xBB; 4]);
+    assert_eq!(buf.len(), 8 + 38 + 4);
+    assert_eq!(buf[..8], [0xAA; 8]);
+    assert_eq!(buf[buf.len() - 4..], [0xBB; 4]);
+    assert_eq!(buf.headroom(), 0);
+    buf.pull_front(8);
+    buf.truncate(38);
+    assert_eq!(buf[0] >> 4, 4);
+
+    // Echo it back by swapping its addresses and ports, which keeps the checksums valid.
+    let (src, dst) = buf[12..20].split_at_mut(4);
+    src.swap_with_slice(dst);
+    let (src_port, dst_port) = buf[20..24].split_at_mut(2);
+    src_port.swap_with_slice(dst_port);
+    assert_eq!(tun.send(&buf).unwrap(), 38);
+
+    let mut payload = [0; 100];
+    let (n, _) = udp_socket.recv_from(&mut payload).unwrap();
+    assert_eq!(payload[..n], [1; 10]);
+
+    // Dropped buffers go back to the pool, emptied.
+    drop(buf);
+    assert_eq!(pool.idle(), 1);
+    let buf = pool.get();
+    assert!(buf.is_empty());
+    assert_eq!(buf.headroom(), 8);
+    assert_eq!(pool.idle(), 0);
+
+    // The length of a packet can't reach into the reserved tailroom.
+    let mut buf = PacketBuf::with_room(100, 8, 4);
+    buf.set_len(100);
+    assert!(std::panic::catch_unwind(move || buf.set_len(101)).is_err());
+
+    // Buffers returned to a full pool are freed.
+    let pool = BufferPool::with_max_idle(1500, 0, 0, 2);
+    let bufs: Vec<_> = (0..4).map(|_| pool.get()).collect();
+    drop(bufs);
+    assert_eq!(pool.idle(), pool.max_idle());
+}
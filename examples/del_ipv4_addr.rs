@@ -10,8 +10,6 @@ fn main() {
 
     let err = tun.get_addr().unwrap_err();
 
-    assert!(matches!(
-        err,
-        error::Error::NixError(nix::errno::Errno::EADDRNOTAVAIL)
-    ))
+    assert_eq!(err.errno(), Some(nix::errno::Errno::EADDRNOTAVAIL));
+    assert_eq!(err.op(), Some("SIOCGIFADDR"));
 }
@@ -0,0 +1,33 @@
+use tidy_tuntap::error::Error;
+use tidy_tuntap::*;
+
+fn main() {
+    let tun = Tun::new("tun10", false).unwrap();
+    tun.persist(true).unwrap();
+
+    // The device is still open.
+    assert!(matches!(delete_device("tun10"), Err(Error::DeviceBusy(_))));
+
+    // The device outlives the file descriptor since it's persistent.
+    drop(tun);
+    assert!(list_interfaces().unwrap().iter().any(|i| i.name == "tun10"));
+
+    delete_device("tun10").unwrap();
+    assert!(list_interfaces().unwrap().iter().all(|i| i.name != "tun10"));
+
+    assert!(matches!(
+        delete_device("tun10"),
+        Err(Error::DeviceNotFound(_))
+    ));
+    assert!(matches!(delete_device("lo"), Err(Error::NotTunTap(_))));
+
+    // Queues of a multiqueue device can be attached to while others are open, which still
+    // counts as busy, and leaves the device persistent.
+    let tuns = MQTun::new("tun11", 2, false).unwrap();
+    tuns[0].persist(true).unwrap();
+    assert!(matches!(delete_device("tun11"), Err(Error::DeviceBusy(_))));
+
+    drop(tuns);
+    assert!(list_interfaces().unwrap().iter().any(|i| i.name == "tun11"));
+    delete_device("tun11").unwrap();
+}
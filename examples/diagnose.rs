@@ -0,0 +1,25 @@
+use tidy_tuntap::diagnostics::{CheckKind, Severity};
+use tidy_tuntap::*;
+
+fn main() {
+    let report = diagnose(Some("tun10"));
+    assert!(report.is_ok());
+    let check = report.get(CheckKind::InterfaceName).unwrap();
+    assert_eq!(check.severity, Severity::Ok);
+
+    // The name of an existing TUN/TAP interface is in use.
+    let _tun = Tun::new("tun10", false).unwrap();
+
+    let report = diagnose(Some("tun10"));
+    let check = report.get(CheckKind::InterfaceName).unwrap();
+    assert_eq!(check.severity, Severity::Warning);
+
+    let report = diagnose(Some("lo"));
+    let check = report.get(CheckKind::InterfaceName).unwrap();
+    assert_eq!(check.severity, Severity::Error);
+
+    let report = diagnose(Some("a/b"));
+    assert!(!report.is_ok());
+    let check = report.get(CheckKind::InterfaceName).unwrap();
+    assert_eq!(check.severity, Severity::Error);
+}
@@ -0,0 +1,26 @@
+use tidy_tuntap::*;
+
+fn main() {
+    let tun = Tun::new("tun10", false).unwrap();
+
+    // A single queue device can't be attached to twice.
+    let err = Tun::new("tun10", false).unwrap_err();
+    assert!(err.is_name_in_use());
+    assert_eq!(err.op(), Some("TUNSETIFF"));
+    assert_eq!(err.interface_name(), Some("tun10"));
+
+    // The name belongs to an interface which is not a TUN/TAP interface.
+    let err = Tap::new("lo", false).unwrap_err();
+    assert!(err.is_name_in_use());
+
+    let err = tun.set_mtu(-1).unwrap_err();
+    assert!(!err.is_device_gone());
+    assert_eq!(err.op(), Some("SIOCSIFMTU"));
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "SIOCSIFMTU failed on interface tun10: {}",
+            nix::errno::Errno::EINVAL
+        )
+    );
+}
@@ -0,0 +1,53 @@
+use std::net::Ipv4Addr;
+
+use tidy_tuntap::events::Event;
+use tidy_tuntap::*;
+
+fn main() {
+    let tun = Tun::new("tun10", false).unwrap();
+    let mut events = tun.events().unwrap();
+
+    tun.bring_up().unwrap();
+    tun.set_mtu(1400).unwrap();
+    tun.set_addr(Ipv4Addr::new(10, 10, 10, 1)).unwrap();
+
+    let (mut up, mut mtu_changed, mut addr_added) = (false, false, false);
+
+    // Other events (e.g. the IPv6 link-local address being added) may come in between.
+    while !(up && mtu_changed && addr_added) {
+        match events.next_event().unwrap() {
+            Event::Up => up = true,
+            Event::MtuChanged(mtu) => mtu_changed = mtu == 1400,
+            Event::AddressAdded { addr, .. } => addr_added |= addr == Ipv4Addr::new(10, 10, 10, 1),
+            _ => {}
+        }
+    }
+
+    // An IPv6 address is notified again once duplicate address detection completes, but
+    // it's only reported as added once.
+    let addr = "fd00::1".parse().unwrap();
+    tun.set_ipv6_addr(addr).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    tun.del_ipv6_addr(addr).unwrap();
+
+    let mut added = 0;
+    loop {
+        match events.next_event().unwrap() {
+            Event::AddressAdded {
+                addr: added_addr, ..
+            } if added_addr == addr => added += 1,
+            Event::AddressRemoved {
+                addr: removed_addr, ..
+            } if removed_addr == addr => break,
+            _ => {}
+        }
+    }
+    assert_eq!(added, 1);
+
+    tun.bring_down().unwrap();
+    loop {
+        if events.next_event().unwrap() == Event::Down {
+            break;
+        }
+    }
+}
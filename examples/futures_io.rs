@@ -0,0 +1,67 @@
+use std::net::Ipv4Addr;
+
+use futures::{AsyncReadExt, AsyncWriteExt};
+use tidy_tuntap::error::Error;
+use tidy_tuntap::*;
+use tokio::net::UdpSocket;
+
+#[tokio::main]
+async fn main() {
+    let mut tun = AsyncTun::new("tun10", false).unwrap();
+    tun.bring_up().unwrap();
+    tun.set_addr(Ipv4Addr::new(10, 10, 10, 1)).unwrap();
+    tun.set_netmask(Ipv4Addr::new(255, 255, 255, 0)).unwrap();
+
+    let udp_socket = UdpSocket::bind("10.10.10.1:33333").await.unwrap();
+
+    // A packet which doesn't fit into the buffer is reported instead of silently truncated.
+    udp_socket
+        .send_to(&[1; 10], "10.10.10.2:44444")
+        .await
+        .unwrap();
+    let err = tun.read(&mut [0; 20]).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    let inner = err.into_inner().unwrap().downcast::<Error>().unwrap();
+    assert!(matches!(*inner, Error::PacketTruncated(20)));
+
+    // So is it through the `tokio` traits.
+    udp_socket
+        .send_to(&[1; 10], "10.10.10.2:44444")
+        .await
+        .unwrap();
+    let err = tokio::io::AsyncReadExt::read(&mut *tun, &mut [0; 20])
+        .await
+        .unwrap_err();
+    let inner = err.into_inner().unwrap().downcast::<Error>().unwrap();
+    assert!(matches!(*inner, Error::PacketTruncated(20)));
+
+    // Each read returns a single packet through the `futures` traits.
+    udp_socket
+        .send_to(&[2; 10], "10.10.10.2:44444")
+        .await
+        .unwrap();
+    let mut buf = [0; 1500];
+    loop {
+        let n = tun.read(&mut buf).await.unwrap();
+
+        if n == 38 && buf[9] == 17 && buf[28..38] == [2; 10] {
+            break;
+        }
+    }
+
+    // Echo it back through the write half, by swapping its addresses and ports which keeps
+    // the checksums valid.
+    let (src, dst) = buf[12..20].split_at_mut(4);
+    src.swap_with_slice(dst);
+    let (src_port, dst_port) = buf[20..24].split_at_mut(2);
+    src_port.swap_with_slice(dst_port);
+
+    // `AsyncReadExt::split` would take precedence over the inherent method.
+    let (_reader, mut writer) = AsyncDevice::split(&tun);
+    writer.write_all(&buf[..38]).await.unwrap();
+
+    let mut payload = [0; 100];
+    let (n, _) = udp_socket.recv_from(&mut payload).await.unwrap();
+    assert_eq!(payload[..n], [2; 10]);
+}
@@ -0,0 +1,46 @@
+use std::thread;
+
+use tidy_tuntap::error::Error;
+use tidy_tuntap::*;
+
+fn main() {
+    let tun = Tun::new("tun10", false).unwrap();
+    let handle = tun.handle().clone();
+
+    // Configure the interface from another thread while this one owns the device.
+    thread::spawn(move || {
+        handle.set_mtu(1400).unwrap();
+        handle.bring_up().unwrap();
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(tun.get_mtu().unwrap(), 1400);
+
+    let by_name = InterfaceHandle::from_name("tun10").unwrap();
+    let index = by_name.get_index().unwrap();
+    assert!(by_name.flags().unwrap().contains(flags::Flags::IFF_UP));
+
+    let by_index = InterfaceHandle::from_index(index as u32).unwrap();
+    assert_eq!(by_index.name(), "tun10");
+    assert_eq!(&by_index, tun.handle());
+
+    // The handle outlives the device, but the interface doesn't.
+    drop(tun);
+    assert!(by_index.get_mtu().unwrap_err().is_device_gone());
+
+    assert!(matches!(
+        InterfaceHandle::from_name("tun10"),
+        Err(Error::DeviceNotFound(_))
+    ));
+
+    // A new interface with the same name is not the one the handle refers to.
+    let tun = Tun::new("tun10", false).unwrap();
+    assert_ne!(&by_index, tun.handle());
+    assert!(matches!(by_index.get_mtu(), Err(Error::DeviceNotFound(_))));
+    assert!(matches!(
+        by_index.current_name(),
+        Err(Error::DeviceNotFound(_))
+    ));
+    assert_eq!(tun.current_name().unwrap(), "tun10");
+}
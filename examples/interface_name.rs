@@ -0,0 +1,32 @@
+use tidy_tuntap::error::Error;
+use tidy_tuntap::*;
+
+fn main() {
+    for name in [
+        "",
+        ".",
+        "..",
+        "a/b",
+        "a:b",
+        "a b",
+        "a_very_long_name",
+        "tun%s",
+        "%d%d",
+    ] {
+        assert!(
+            matches!(Tun::new(name, false), Err(Error::InvalidInterfaceName(..))),
+            "{name:?} must be rejected"
+        );
+    }
+
+    let name: InterfaceName = "tun%d".parse().unwrap();
+    assert!(name.is_template());
+
+    // The kernel completes the template.
+    let tun = Tun::new(&name, false).unwrap();
+    assert!(!tun.name().is_template());
+    assert!(tun.name().starts_with("tun"));
+
+    let tun = Tun::new("tun10", false).unwrap();
+    assert_eq!(tun.name(), "tun10");
+}
@@ -0,0 +1,25 @@
+use tidy_tuntap::flags::TunFlags;
+use tidy_tuntap::*;
+
+fn main() {
+    let tun = Tun::new("tun10", false).unwrap();
+    let _mq = MQTap::new("tap10", 3, true).unwrap();
+
+    let interfaces = list_interfaces().unwrap();
+
+    let tun_interface = interfaces.iter().find(|i| i.name == "tun10").unwrap();
+    assert_eq!(tun_interface.index, tun.get_index().unwrap());
+    assert_eq!(tun_interface.mode, Mode::Tun);
+    assert!(tun_interface
+        .flags
+        .contains(TunFlags::IFF_TUN | TunFlags::IFF_NO_PI));
+    assert!(!tun_interface.is_persistent());
+    assert_eq!(tun_interface.queues, None);
+
+    let mq_interface = interfaces.iter().find(|i| i.name == "tap10").unwrap();
+    assert_eq!(mq_interface.mode, Mode::Tap);
+    assert!(mq_interface.flags.contains(TunFlags::IFF_MULTI_QUEUE));
+    assert!(!mq_interface.flags.contains(TunFlags::IFF_NO_PI));
+    assert_eq!(mq_interface.queues, Some(3));
+    assert_eq!(mq_interface.disabled_queues, Some(0));
+}
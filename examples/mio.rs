@@ -0,0 +1,57 @@
+use std::net::{Ipv4Addr, UdpSocket};
+
+use mio::{Events, Interest, Poll, Token};
+use nix::errno::Errno;
+use tidy_tuntap::*;
+
+fn main() {
+    let mut tun = Tun::new_nonblocking("tun10", false).unwrap();
+    tun.bring_up().unwrap();
+    tun.set_addr(Ipv4Addr::new(10, 10, 10, 1)).unwrap();
+    tun.set_netmask(Ipv4Addr::new(255, 255, 255, 0)).unwrap();
+
+    let mut poll = Poll::new().unwrap();
+    poll.registry()
+        .register(&mut tun, Token(0), Interest::READABLE)
+        .unwrap();
+
+    let udp_socket = UdpSocket::bind("10.10.10.1:33333").unwrap();
+    udp_socket.send_to(&[1; 10], "10.10.10.2:44444").unwrap();
+
+    // Drain the device on every event until our packet shows up, skipping the unrelated ones.
+    let mut events = Events::with_capacity(8);
+    let mut buf = [0; 1500];
+    'outer: loop {
+        poll.poll(&mut events, None).unwrap();
+
+        for event in &events {
+            assert_eq!(event.token(), Token(0));
+
+            loop {
+                match tun.recv(&mut buf) {
+                    Ok(n) if n == 38 && buf[9] == 17 => {
+                        assert_eq!(buf[28..38], [1; 10]);
+                        break 'outer;
+                    }
+                    Ok(_) => continue,
+                    Err(err) if err.errno() == Some(Errno::EAGAIN) => break,
+                    Err(err) => panic!("{}", err),
+                }
+            }
+        }
+    }
+
+    poll.registry().deregister(&mut tun).unwrap();
+
+    // Each queue of a multiqueue device is a separate source.
+    let mut queues = MQTun::new_nonblocking("mqtun10", 2, false).unwrap();
+    for (i, queue) in queues.iter_mut().enumerate() {
+        poll.registry()
+            .register(queue, Token(i + 1), Interest::READABLE)
+            .unwrap();
+    }
+    assert_eq!(
+        queues[0].recv(&mut buf).unwrap_err().errno(),
+        Some(Errno::EAGAIN)
+    );
+}
@@ -0,0 +1,51 @@
+use std::net::{Ipv4Addr, UdpSocket};
+use std::time::Duration;
+
+use tidy_tuntap::*;
+
+fn main() {
+    // A multiqueue device can start with a single queue.
+    let mut queues = MQTun::new("tun10", 1, false).unwrap();
+    queues[0].bring_up().unwrap();
+    queues[0].set_addr(Ipv4Addr::new(10, 10, 10, 1)).unwrap();
+    queues[0]
+        .set_netmask(Ipv4Addr::new(255, 255, 255, 0))
+        .unwrap();
+    assert_eq!(queues[0].queue_count().unwrap(), 1);
+
+    // More queues are added as the load increases.
+    for count in 2..=4 {
+        let queue = queues[0].add_queue().unwrap();
+        queues.push(queue);
+
+        assert_eq!(queues[0].queue_count().unwrap(), count);
+    }
+
+    // Detached queues are not counted, and dropped queues are removed.
+    queues[1].detach().unwrap();
+    assert_eq!(queues[0].queue_count().unwrap(), 3);
+    queues[1].attach().unwrap();
+    assert_eq!(queues[0].queue_count().unwrap(), 4);
+
+    queues.truncate(2);
+    assert_eq!(queues[0].queue_count().unwrap(), 2);
+
+    // Only the added queue is attached, so it receives all the packets.
+    queues[0].detach().unwrap();
+
+    let udp_socket = UdpSocket::bind("10.10.10.1:33333").unwrap();
+    udp_socket.send_to(&[1; 10], "10.10.10.2:44444").unwrap();
+
+    let mut buf = [0; 1500];
+    loop {
+        let n = queues[1]
+            .recv_timeout(&mut buf, Duration::from_secs(5))
+            .unwrap();
+        let ihl = (buf[0] & 0xf) as usize * 4;
+
+        if buf[0] >> 4 == 4 && buf[9] == 17 && buf[ihl + 2..ihl + 4] == 44444u16.to_be_bytes() {
+            assert_eq!(buf[ihl + 8..n], [1; 10]);
+            break;
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+use tidy_tuntap::neighbor::{Neighbor, NeighborState};
+use tidy_tuntap::*;
+
+fn main() {
+    let tap = Tap::new("tap10", false).unwrap();
+    tap.bring_up().unwrap();
+    tap.set_addr(Ipv4Addr::new(10, 10, 10, 1)).unwrap();
+    tap.set_netmask(Ipv4Addr::new(255, 255, 255, 0)).unwrap();
+
+    let arp = Neighbor::new(Ipv4Addr::new(10, 10, 10, 2), [2, 0, 0, 0, 0, 2]);
+    let ndp = Neighbor {
+        state: NeighborState::Stale,
+        ..Neighbor::new(Ipv6Addr::from_str("fe80::2").unwrap(), [2, 0, 0, 0, 0, 3])
+    };
+
+    tap.add_neighbor(&arp).unwrap();
+    tap.add_neighbor(&ndp).unwrap();
+
+    let neighbors = tap.get_neighbors().unwrap();
+    assert!(neighbors.contains(&arp));
+    assert!(neighbors.contains(&ndp));
+
+    tap.del_neighbor(&arp).unwrap();
+    tap.del_neighbor(&ndp).unwrap();
+
+    let neighbors = tap.get_neighbors().unwrap();
+    assert!(!neighbors.iter().any(|n| n.addr == arp.addr));
+    assert!(!neighbors.iter().any(|n| n.addr == ndp.addr));
+}
@@ -0,0 +1,38 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+use tidy_tuntap::route::Route;
+use tidy_tuntap::*;
+
+fn main() {
+    let tun = Tun::new("tun10", false).unwrap();
+    tun.bring_up().unwrap();
+    tun.set_addr(Ipv4Addr::new(10, 10, 10, 1)).unwrap();
+    tun.set_netmask(Ipv4Addr::new(255, 255, 255, 0)).unwrap();
+
+    let route_v4 = Route {
+        gateway: Some(Ipv4Addr::new(10, 10, 10, 2).into()),
+        metric: Some(100),
+        table: 100,
+        ..Route::new(Ipv4Addr::new(10, 20, 0, 0), 16)
+    };
+    let route_v6 = Route::new(Ipv6Addr::from_str("fd00:1::").unwrap(), 64);
+
+    tun.add_route(&route_v4).unwrap();
+    tun.add_route(&route_v6).unwrap();
+
+    let routes = tun.get_routes().unwrap();
+    assert!(routes.contains(&route_v4));
+    assert!(routes
+        .iter()
+        .any(|route| route.destination == route_v6.destination && route.prefix_len == 64));
+
+    tun.del_route(&route_v4).unwrap();
+    tun.del_route(&route_v6).unwrap();
+
+    let routes = tun.get_routes().unwrap();
+    assert!(!routes.contains(&route_v4));
+    assert!(!routes
+        .iter()
+        .any(|route| route.destination == route_v6.destination));
+}
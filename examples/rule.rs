@@ -0,0 +1,44 @@
+use std::net::Ipv4Addr;
+
+use tidy_tuntap::route::Route;
+use tidy_tuntap::rule::{Family, Rule};
+use tidy_tuntap::*;
+
+fn main() {
+    let tun = Tun::new("tun10", false).unwrap();
+    tun.bring_up().unwrap();
+    tun.set_addr(Ipv4Addr::new(10, 10, 10, 1)).unwrap();
+    tun.set_netmask(Ipv4Addr::new(255, 255, 255, 0)).unwrap();
+
+    let route = Route {
+        table: 100,
+        ..Route::new(Ipv4Addr::UNSPECIFIED, 0)
+    };
+    tun.add_route(&route).unwrap();
+
+    let fwmark_rule = Rule {
+        priority: Some(1000),
+        fwmark: Some(0x10),
+        ..Rule::new(Family::Ipv4, 100)
+    };
+    let source_rule = Rule {
+        priority: Some(1001),
+        source: Some((Ipv4Addr::new(192, 168, 50, 0).into(), 24)),
+        uid_range: Some(1000..=2000),
+        ..Rule::new(Family::Ipv4, 100)
+    };
+
+    tun.add_rule(&fwmark_rule).unwrap();
+    tun.add_rule(&source_rule).unwrap();
+
+    let rules = tun.get_rules().unwrap();
+    assert!(rules.contains(&fwmark_rule));
+    assert!(rules.contains(&source_rule));
+
+    tun.del_rule(&fwmark_rule).unwrap();
+    tun.del_rule(&source_rule).unwrap();
+
+    let rules = tun.get_rules().unwrap();
+    assert!(!rules.contains(&fwmark_rule));
+    assert!(!rules.contains(&source_rule));
+}
@@ -0,0 +1,53 @@
+use std::net::Ipv4Addr;
+
+use tidy_tuntap::*;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UdpSocket;
+
+#[tokio::main]
+async fn main() {
+    let tun = AsyncTun::new("tun10", false).unwrap();
+    tun.bring_up().unwrap();
+    tun.set_addr(Ipv4Addr::new(10, 10, 10, 1)).unwrap();
+    tun.set_netmask(Ipv4Addr::new(255, 255, 255, 0)).unwrap();
+
+    let (mut reader, mut writer) = tun.into_split();
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<Vec<u8>>(1);
+
+    // Reads the UDP packets sent to 10.10.10.2:44444 and passes them to the writer.
+    tokio::spawn(async move {
+        let mut buf = [0; 1500];
+        loop {
+            let n = reader.read(&mut buf).await.unwrap();
+            let ihl = (buf[0] & 0xf) as usize * 4;
+
+            if buf[0] >> 4 == 4 && buf[9] == 17 && buf[ihl + 2..ihl + 4] == 44444u16.to_be_bytes() {
+                tx.send(buf[..n].to_vec()).await.unwrap();
+            }
+        }
+    });
+
+    // Echoes the packets back by swapping their addresses and ports, which keeps the checksums valid.
+    tokio::spawn(async move {
+        while let Some(mut packet) = rx.recv().await {
+            let ihl = (packet[0] & 0xf) as usize * 4;
+            let (src, dst) = packet[12..20].split_at_mut(4);
+            src.swap_with_slice(dst);
+            let (src_port, dst_port) = packet[ihl..ihl + 4].split_at_mut(2);
+            src_port.swap_with_slice(dst_port);
+
+            writer.write_all(&packet).await.unwrap();
+        }
+    });
+
+    let udp_socket = UdpSocket::bind("10.10.10.1:33333").await.unwrap();
+    udp_socket
+        .send_to(&[1; 10], "10.10.10.2:44444")
+        .await
+        .unwrap();
+
+    let mut buf = [0; 100];
+    let (n, from) = udp_socket.recv_from(&mut buf).await.unwrap();
+    assert_eq!(buf[..n], [1; 10]);
+    assert_eq!(from, "10.10.10.2:44444".parse().unwrap());
+}
@@ -0,0 +1,24 @@
+use std::net::{Ipv4Addr, UdpSocket};
+
+use tidy_tuntap::*;
+
+fn main() {
+    let tun = Tun::new("tun10", false).unwrap();
+    tun.bring_up().unwrap();
+    tun.set_addr(Ipv4Addr::new(10, 10, 10, 1)).unwrap();
+    tun.set_netmask(Ipv4Addr::new(255, 255, 255, 0)).unwrap();
+
+    let before = tun.stats().unwrap();
+
+    let udp_socket = UdpSocket::bind("10.10.10.1:33333").unwrap();
+    udp_socket.send_to(&[1; 10], "10.10.10.2:44444").unwrap();
+
+    let mut buf = [0; 1500];
+    let bytes_read = tun.recv(&mut buf).unwrap();
+
+    let after = tun.stats().unwrap();
+    assert!(after.tx_packets > before.tx_packets);
+    assert!(after.tx_bytes >= before.tx_bytes + bytes_read as u64);
+    assert_eq!(after.tx_dropped, before.tx_dropped);
+    assert_eq!(after.rx_frame_errors, before.rx_frame_errors);
+}
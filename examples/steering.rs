@@ -0,0 +1,69 @@
+use tidy_tuntap::*;
+
+fn main() {
+    let mut queues = MQTun::new("tun10", 3, false).unwrap();
+    for (index, queue) in queues.iter().enumerate() {
+        assert_eq!(queue.queue_index(), Some(index));
+    }
+
+    // The last queue takes the index of a detached queue.
+    queues[0].detach().unwrap();
+    assert_eq!(queues[0].queue_index(), None);
+    assert_eq!(queues[1].queue_index(), Some(1));
+    assert_eq!(queues[2].queue_index(), Some(0));
+
+    // A reattached queue takes the next index, and so does an added one.
+    queues[0].attach().unwrap();
+    assert_eq!(queues[0].queue_index(), Some(2));
+
+    let queue = queues[0].add_queue().unwrap();
+    assert_eq!(queue.queue_index(), Some(3));
+    queues.push(queue);
+
+    // The same goes for a dropped queue.
+    queues.swap_remove(1);
+    assert_eq!(queues[1].queue_index(), Some(1));
+
+    // A clone keeps the queue in the interface after the original is dropped.
+    let clone = queues[1].try_clone().unwrap();
+    drop(std::mem::replace(&mut queues[1], clone));
+    assert_eq!(queues[1].queue_index(), Some(1));
+    assert_eq!(queues[1].queue_count().unwrap(), 3);
+
+    // Writing the sysfs files requires root, while the other examples only need CAP_NET_ADMIN.
+    match queues[0].set_rps_flow_cnt(0) {
+        Err(err) if err.is_permission_denied() => return,
+        result => result.unwrap(),
+    }
+
+    // Steer the packets written to each queue to the CPU its worker would be pinned to. The
+    // XPS setting has no effect on TUN/TAP devices, but it's still stored by the kernel.
+    let cpus = allowed_cpus().unwrap();
+    for queue in &queues {
+        let cpu = cpus[queue.queue_index().unwrap() % cpus.len()];
+
+        queue.set_xps_cpus(&[cpu]).unwrap();
+        assert_eq!(queue.get_xps_cpus().unwrap(), [cpu]);
+
+        queue.set_rps_cpus(&[cpu]).unwrap();
+        assert_eq!(queue.get_rps_cpus().unwrap(), [cpu]);
+
+        queue.set_rps_flow_cnt(256).unwrap();
+        assert_eq!(queue.get_rps_flow_cnt().unwrap(), 256);
+    }
+
+    // The settings follow a queue which takes the index of a detached one.
+    for (i, queue) in queues.iter().enumerate() {
+        queue.set_rps_flow_cnt(256 << i).unwrap();
+    }
+    assert_eq!(queues[2].queue_index(), Some(0));
+    assert_eq!(queues[0].queue_index(), Some(2));
+    queues[2].detach().unwrap();
+    assert_eq!(queues[0].queue_index(), Some(0));
+    assert_eq!(queues[0].get_rps_flow_cnt().unwrap(), 256);
+
+    assert!(matches!(
+        queues[2].set_xps_cpus(&[0]),
+        Err(error::Error::QueueDetached(_))
+    ));
+}
@@ -0,0 +1,24 @@
+use tidy_tuntap::sysctl::{AcceptDad, AddrGenMode, RpFilter};
+use tidy_tuntap::*;
+
+fn main() {
+    let tun = Tun::new("tun10", false).unwrap();
+
+    tun.set_addr_gen_mode(AddrGenMode::None).unwrap();
+    tun.set_accept_dad(AcceptDad::Disabled).unwrap();
+    tun.set_rp_filter(RpFilter::Loose).unwrap();
+    tun.set_ipv4_forwarding(true).unwrap();
+
+    assert_eq!(tun.get_addr_gen_mode().unwrap(), AddrGenMode::None);
+    assert_eq!(tun.get_accept_dad().unwrap(), AcceptDad::Disabled);
+    assert_eq!(tun.get_rp_filter().unwrap(), RpFilter::Loose);
+    assert!(tun.get_ipv4_forwarding().unwrap());
+
+    tun.set_disable_ipv6(true).unwrap();
+    assert!(tun.get_disable_ipv6().unwrap());
+
+    // No link-local address should be generated when the device is brought up.
+    tun.set_disable_ipv6(false).unwrap();
+    tun.bring_up().unwrap();
+    assert!(tun.get_ipv6_addrs().unwrap().is_empty());
+}
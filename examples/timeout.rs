@@ -0,0 +1,61 @@
+use std::net::{Ipv4Addr, UdpSocket};
+use std::time::{Duration, Instant};
+
+use nix::errno::Errno;
+use tidy_tuntap::error::Error;
+use tidy_tuntap::*;
+
+fn main() {
+    // The interface is down, so no packet arrives.
+    let tun = Tun::new("tun10", false).unwrap();
+    let mut buf = [0; 1500];
+
+    assert!(!tun.readable().unwrap());
+
+    let start = Instant::now();
+    let err = tun
+        .recv_timeout(&mut buf, Duration::from_millis(100))
+        .unwrap_err();
+    assert!(matches!(err, Error::TimedOut));
+    assert!(start.elapsed() >= Duration::from_millis(100));
+
+    // In non-blocking mode, reading fails right away.
+    tun.set_nonblocking(true).unwrap();
+    assert_eq!(tun.recv(&mut buf).unwrap_err().errno(), Some(Errno::EAGAIN));
+    let err = tun
+        .recv_timeout(&mut buf, Duration::from_millis(10))
+        .unwrap_err();
+    assert!(matches!(err, Error::TimedOut));
+    tun.set_nonblocking(false).unwrap();
+
+    tun.bring_up().unwrap();
+    tun.set_addr(Ipv4Addr::new(10, 10, 10, 1)).unwrap();
+    tun.set_netmask(Ipv4Addr::new(255, 255, 255, 0)).unwrap();
+    assert!(tun.writable().unwrap());
+
+    let udp_socket = UdpSocket::bind("10.10.10.1:33333").unwrap();
+    udp_socket.send_to(&[1; 10], "10.10.10.2:44444").unwrap();
+
+    loop {
+        let n = tun.recv_timeout(&mut buf, Duration::from_secs(1)).unwrap();
+
+        if n == 38 && buf[9] == 17 {
+            break;
+        }
+    }
+
+    // Echo it back by swapping its addresses and ports, which keeps the checksums valid.
+    let (src, dst) = buf[12..20].split_at_mut(4);
+    src.swap_with_slice(dst);
+    let (src_port, dst_port) = buf[20..24].split_at_mut(2);
+    src_port.swap_with_slice(dst_port);
+    assert_eq!(
+        tun.send_timeout(&buf[..38], Duration::from_secs(1))
+            .unwrap(),
+        38
+    );
+
+    let mut payload = [0; 100];
+    let (n, _) = udp_socket.recv_from(&mut payload).unwrap();
+    assert_eq!(payload[..n], [1; 10]);
+}
@@ -0,0 +1,44 @@
+use std::net::{Ipv4Addr, UdpSocket};
+use std::thread;
+
+use tidy_tuntap::*;
+
+fn main() {
+    let tun = Tun::new("tun10", false).unwrap();
+    tun.bring_up().unwrap();
+    tun.set_addr(Ipv4Addr::new(10, 10, 10, 1)).unwrap();
+    tun.set_netmask(Ipv4Addr::new(255, 255, 255, 0)).unwrap();
+
+    let reader = tun.try_clone().unwrap();
+
+    let udp_socket = UdpSocket::bind("10.10.10.1:33333").unwrap();
+    udp_socket.send_to(&[1; 10], "10.10.10.2:44444").unwrap();
+
+    // Read the packet on another thread, while this thread owns the original device.
+    let mut packet = thread::spawn(move || {
+        let mut buf = [0; 1500];
+        loop {
+            let n = reader.recv(&mut buf).unwrap();
+            let ihl = (buf[0] & 0xf) as usize * 4;
+
+            if buf[0] >> 4 == 4 && buf[9] == 17 && buf[ihl + 2..ihl + 4] == 44444u16.to_be_bytes() {
+                return buf[..n].to_vec();
+            }
+        }
+    })
+    .join()
+    .unwrap();
+
+    // Echo the packet back by swapping its addresses and ports, which keeps the checksums valid.
+    let ihl = (packet[0] & 0xf) as usize * 4;
+    let (src, dst) = packet[12..20].split_at_mut(4);
+    src.swap_with_slice(dst);
+    let (src_port, dst_port) = packet[ihl..ihl + 4].split_at_mut(2);
+    src_port.swap_with_slice(dst_port);
+    tun.send(&packet).unwrap();
+
+    let mut buf = [0; 100];
+    let (n, from) = udp_socket.recv_from(&mut buf).unwrap();
+    assert_eq!(buf[..n], [1; 10]);
+    assert_eq!(from, "10.10.10.2:44444".parse().unwrap());
+}
@@ -0,0 +1,58 @@
+use std::net::{Ipv4Addr, UdpSocket};
+
+use tidy_tuntap::uring::IoUring;
+use tidy_tuntap::*;
+
+fn main() {
+    let tun = Tun::new("tun10", false).unwrap();
+    tun.bring_up().unwrap();
+    tun.set_addr(Ipv4Addr::new(10, 10, 10, 1)).unwrap();
+    tun.set_netmask(Ipv4Addr::new(255, 255, 255, 0)).unwrap();
+
+    let mut uring = IoUring::new(&tun, 8, 1500).unwrap();
+
+    let udp_socket = UdpSocket::bind("10.10.10.1:33333").unwrap();
+    for i in 0..4 {
+        udp_socket.send_to(&[i; 10], "10.10.10.2:44444").unwrap();
+    }
+
+    // Keep reading until all of our packets are drained, skipping the unrelated ones.
+    let mut packets = Vec::new();
+    while packets.len() < 4 {
+        let batch = uring.recv_batch().unwrap();
+        assert!(batch.len() >= 1);
+
+        packets.extend(
+            batch
+                .filter(|packet| packet[0] >> 4 == 4 && packet[9] == 17)
+                .map(|packet| packet.to_vec()),
+        );
+    }
+    for (i, packet) in packets.iter().enumerate() {
+        assert_eq!(packet[28..], [i as u8; 10]);
+    }
+
+    // Echo them back by swapping their addresses and ports, which keeps the checksums valid.
+    for packet in &mut packets {
+        let (src, dst) = packet[12..20].split_at_mut(4);
+        src.swap_with_slice(dst);
+        let (src_port, dst_port) = packet[20..24].split_at_mut(2);
+        src_port.swap_with_slice(dst_port);
+    }
+    assert_eq!(uring.send_batch(&packets).unwrap(), 4);
+
+    let mut buf = [0; 100];
+    for i in 0..4 {
+        let (n, from) = udp_socket.recv_from(&mut buf).unwrap();
+        assert_eq!(buf[..n], [i; 10]);
+        assert_eq!(from, "10.10.10.2:44444".parse().unwrap());
+    }
+
+    // Packets longer than the buffers are rejected.
+    let err = uring.send_batch(&[[0; 2000]]).unwrap_err();
+    assert_eq!(err.errno(), Some(nix::errno::Errno::EMSGSIZE));
+
+    // The buffers are indexed by 16 bits, which limits the number of entries.
+    let err = IoUring::new(&tun, 40000, 1500).unwrap_err();
+    assert_eq!(err.errno(), Some(nix::errno::Errno::EINVAL));
+}
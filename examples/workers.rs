@@ -0,0 +1,160 @@
+use std::net::{Ipv4Addr, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tidy_tuntap::error::Error;
+use tidy_tuntap::*;
+
+fn main() {
+    let queues = MQTun::new("tun10", 3, false).unwrap();
+    queues[0].bring_up().unwrap();
+    queues[0].set_addr(Ipv4Addr::new(10, 10, 10, 1)).unwrap();
+    queues[0]
+        .set_netmask(Ipv4Addr::new(255, 255, 255, 0))
+        .unwrap();
+
+    let options = WorkerOptions {
+        cpus: allowed_cpus().unwrap(),
+        idle_timeout: None,
+    };
+
+    // Echoes the UDP packets sent to 10.10.10.2:44444 back by swapping their addresses and
+    // ports, which keeps the checksums valid.
+    let workers = Workers::spawn(queues, options, |_, queue, packet| {
+        let ihl = (packet[0] & 0xf) as usize * 4;
+
+        if packet[0] >> 4 == 4
+            && packet[9] == 17
+            && packet[ihl + 2..ihl + 4] == 44444u16.to_be_bytes()
+        {
+            let (src, dst) = packet[12..20].split_at_mut(4);
+            src.swap_with_slice(dst);
+            let (src_port, dst_port) = packet[ihl..ihl + 4].split_at_mut(2);
+            src_port.swap_with_slice(dst_port);
+
+            queue.send(packet)?;
+        }
+
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(workers.running(), 3);
+
+    // Flows from different ports are spread over the queues.
+    for port in 33331..33337 {
+        let udp_socket = UdpSocket::bind(("10.10.10.1", port)).unwrap();
+        udp_socket
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        udp_socket
+            .send_to(&port.to_be_bytes(), "10.10.10.2:44444")
+            .unwrap();
+
+        let mut buf = [0; 100];
+        let (n, from) = udp_socket.recv_from(&mut buf).unwrap();
+        assert_eq!(buf[..n], port.to_be_bytes());
+        assert_eq!(from, "10.10.10.2:44444".parse().unwrap());
+    }
+
+    // Shutting down returns the queues.
+    let queues: Vec<MQTun> = workers
+        .shutdown()
+        .into_iter()
+        .map(|result| result.map_err(|(_, err)| err).unwrap())
+        .collect();
+    assert_eq!(queues.len(), 3);
+
+    // CPUs outside of the allowed set are rejected before spawning any worker.
+    let options = WorkerOptions {
+        cpus: vec![nix::sched::CpuSet::count() - 1],
+        idle_timeout: None,
+    };
+    assert!(matches!(
+        Workers::spawn(Vec::<MQTun>::new(), options, |_, _, _| Ok(())),
+        Err(Error::CpuNotAllowed(_))
+    ));
+
+    // Idle workers detach their queues and stop.
+    let options = WorkerOptions {
+        cpus: Vec::new(),
+        idle_timeout: Some(Duration::from_millis(300)),
+    };
+    let workers = Workers::spawn(queues, options, |_, _, _| Ok(())).unwrap();
+
+    let queues: Vec<MQTun> = workers
+        .join()
+        .into_iter()
+        .map(|result| result.map_err(|(_, err)| err).unwrap())
+        .collect();
+    assert_eq!(queues[0].queue_count().unwrap(), 0);
+
+    // Errors are reported per queue, without stopping the other workers.
+    queues.iter().for_each(|queue| queue.attach().unwrap());
+    let workers = Workers::spawn(queues, WorkerOptions::default(), |index, _, _| {
+        if index == 0 {
+            Err(Error::TimedOut)
+        } else {
+            Ok(())
+        }
+    })
+    .unwrap();
+
+    // Flows to different ports are spread over the queues, so one of them reaches the first.
+    let udp_socket = UdpSocket::bind("10.10.10.1:0").unwrap();
+    for port in 44444..45444 {
+        if workers.running() < 3 {
+            break;
+        }
+
+        udp_socket.send_to(&[1; 10], ("10.10.10.2", port)).unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(workers.running(), 2, "no packet reached the first queue");
+
+    // The failed queue is returned along with the error.
+    let results = workers.shutdown();
+    assert!(matches!(&results[0], Err((_, Error::TimedOut))));
+    assert!(results[1..].iter().all(Result::is_ok));
+
+    // Packets which don't fit the buffers sized on spawning stop the worker instead of
+    // reaching the handler truncated.
+    let queues = MQTun::new("tun11", 1, false).unwrap();
+    queues[0].bring_up().unwrap();
+    queues[0].set_addr(Ipv4Addr::new(10, 10, 11, 1)).unwrap();
+    queues[0]
+        .set_netmask(Ipv4Addr::new(255, 255, 255, 0))
+        .unwrap();
+
+    let options = WorkerOptions {
+        cpus: Vec::new(),
+        idle_timeout: Some(Duration::from_secs(2)),
+    };
+    let handle = queues[0].handle().clone();
+    let started = Arc::new(AtomicBool::new(false));
+    let workers = Workers::spawn(queues, options, {
+        let started = started.clone();
+        move |_, _, packet| {
+            assert!(packet.len() <= 1500);
+            started.store(true, Ordering::Relaxed);
+            Ok(())
+        }
+    })
+    .unwrap();
+
+    // The buffer is sized once the worker starts, which it did once it handled a packet.
+    let udp_socket = UdpSocket::bind("10.10.11.1:33333").unwrap();
+    while !started.load(Ordering::Relaxed) {
+        udp_socket.send_to(&[1; 10], "10.10.11.2:44444").unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    handle.set_mtu(2000).unwrap();
+    udp_socket.send_to(&[1; 1800], "10.10.11.2:44444").unwrap();
+
+    let results = workers.join();
+    assert!(matches!(
+        &results[0],
+        Err((_, Error::PacketTruncated(1500)))
+    ));
+}
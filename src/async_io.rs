@@ -0,0 +1,221 @@
+//! Async devices for runtimes built on `async-io`, e.g. smol and async-std.
+//!
+//! The devices mirror the tokio based [`AsyncDevice`](crate::AsyncDevice) and implement
+//! the `AsyncRead` and `AsyncWrite` traits of `futures` the same way: each read returns
+//! a single packet, and a packet which doesn't fit into the buffer fails the read with
+//! [`Error::PacketTruncated`](crate::error::Error::PacketTruncated).
+
+use std::io::{self, Write};
+use std::ops;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use ::async_io::Async;
+use futures::io::{AsyncRead, AsyncWrite};
+use nix::errno::Errno;
+
+use crate::common::{create_device, Mode};
+use crate::device::Device;
+use crate::error::{Error, Result};
+use crate::PacketBuf;
+
+/// Represents a non-blocking TUN/TAP device driven by `async-io`.
+///
+/// Contains the shared code between [`AsyncTun`] and [`AsyncTap`].
+#[derive(Debug)]
+pub struct AsyncDevice(Async<Device>);
+impl AsyncDevice {
+    fn new(name: impl AsRef<str>, mode: Mode, packet_info: bool) -> Result<Self> {
+        let (handle, mut files) = create_device(name, mode, 1, packet_info, true, false)?;
+
+        Ok(AsyncDevice(Async::new(Device {
+            handle,
+            file: files.pop().unwrap(),
+        })?))
+    }
+
+    /// Tries to read data from the device and fill the buffer `buf`.
+    ///
+    /// # Returns
+    /// * `Ok`: Containing the number of bytes read from the device.
+    /// * `Err`: If the device was not ready to be read(a `WOULDBLOCK` err), or some other error
+    ///   occurred.
+    pub fn try_recv(&self, buf: &mut [u8]) -> Result<usize> {
+        self.0.get_ref().recv(buf)
+    }
+
+    /// Tries to write data from the buf to the device.
+    ///
+    /// # Returns
+    /// * `Ok`: Containing the number of bytes written to the device.
+    /// * `Err`: If the device was not ready to be written to(a `WOULDBLOCK` err), or some other error
+    ///   occurred.
+    pub fn try_send(&self, buf: &[u8]) -> Result<usize> {
+        self.0.get_ref().send(buf)
+    }
+
+    /// Asyncronously reads data from the device and writes to the `buf`.
+    pub async fn recv(&self, buf: &mut [u8]) -> Result<usize> {
+        self.0
+            .read_with(|device| Ok(device.recv(buf)?))
+            .await
+            .map_err(Error::from_io)
+    }
+
+    /// Asyncronously writes data from `buf` to the device.
+    pub async fn send(&self, buf: &[u8]) -> Result<usize> {
+        self.0
+            .write_with(|device| Ok(device.send(buf)?))
+            .await
+            .map_err(Error::from_io)
+    }
+
+    /// Asyncronously reads a packet from the device and scatters it into `bufs`,
+    /// filling them in order.
+    pub async fn recv_vectored(&self, bufs: &mut [io::IoSliceMut<'_>]) -> Result<usize> {
+        self.0
+            .read_with(|device| Ok(device.recv_vectored(bufs)?))
+            .await
+            .map_err(Error::from_io)
+    }
+
+    /// Asyncronously writes a packet gathered from `bufs` to the device.
+    pub async fn send_vectored(&self, bufs: &[io::IoSlice<'_>]) -> Result<usize> {
+        self.0
+            .write_with(|device| Ok(device.send_vectored(bufs)?))
+            .await
+            .map_err(Error::from_io)
+    }
+
+    /// Asyncronously reads a packet from the device into `buf`, after its reserved headroom.
+    pub async fn recv_buf(&self, buf: &mut PacketBuf) -> Result<usize> {
+        self.0
+            .read_with(|device| Ok(device.recv_buf(buf)?))
+            .await
+            .map_err(Error::from_io)
+    }
+
+    /// Asyncronously waits for a packet, then reads it and the other packets which are ready
+    /// into `bufs`, one packet per buffer.
+    ///
+    /// # Returns
+    /// * `Ok`: Containing the number of filled buffers, which is at least one.
+    /// * `Err`: If reading data was unsuccessful.
+    pub async fn recv_batch(&self, bufs: &mut [PacketBuf]) -> Result<usize> {
+        self.0
+            .read_with(|device| Ok(device.read_batch(bufs, false)?))
+            .await
+            .map_err(Error::from_io)
+    }
+}
+
+impl ops::Deref for AsyncDevice {
+    type Target = Device;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.get_ref()
+    }
+}
+
+impl AsyncRead for AsyncDevice {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        loop {
+            match self.0.get_ref().recv_whole(buf) {
+                Err(err) if err.errno() == Some(Errno::EAGAIN) => {}
+                result => return Poll::Ready(result.map_err(io::Error::from)),
+            }
+
+            futures::ready!(self.0.poll_readable(cx))?;
+        }
+    }
+}
+
+impl AsyncWrite for AsyncDevice {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        loop {
+            match (&self.0.get_ref().file).write(buf) {
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+                result => return Poll::Ready(result),
+            }
+
+            futures::ready!(self.0.poll_writable(cx))?;
+        }
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        loop {
+            match (&self.0.get_ref().file).write_vectored(bufs) {
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+                result => return Poll::Ready(result),
+            }
+
+            futures::ready!(self.0.poll_writable(cx))?;
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Represents a non-blocking TUN device driven by `async-io`.
+#[derive(Debug)]
+pub struct AsyncTun(AsyncDevice);
+impl AsyncTun {
+    pub fn new(name: impl AsRef<str>, packet_info: bool) -> Result<Self> {
+        let device = AsyncDevice::new(name, Mode::Tun, packet_info)?;
+
+        Ok(AsyncTun(device))
+    }
+}
+impl ops::Deref for AsyncTun {
+    type Target = AsyncDevice;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl ops::DerefMut for AsyncTun {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// Represents a non-blocking TAP device driven by `async-io`.
+#[derive(Debug)]
+pub struct AsyncTap(AsyncDevice);
+impl AsyncTap {
+    pub fn new(name: impl AsRef<str>, packet_info: bool) -> Result<Self> {
+        let device = AsyncDevice::new(name, Mode::Tap, packet_info)?;
+
+        Ok(AsyncTap(device))
+    }
+}
+impl ops::Deref for AsyncTap {
+    type Target = AsyncDevice;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl ops::DerefMut for AsyncTap {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
@@ -1,4 +1,4 @@
-use std::io::{self, Read, Write};
+use std::io::{self, Write};
 use std::ops;
 use std::pin::Pin;
 use std::task::{Context, Poll};
@@ -8,7 +8,9 @@ use tokio::io::{AsyncRead, AsyncWrite};
 
 use crate::common::{create_device, Mode};
 use crate::device::Device;
-use crate::error::Result;
+use crate::error::{Error, Result};
+use crate::split::{OwnedReadHalf, OwnedWriteHalf};
+use crate::PacketBuf;
 
 /// Represents a non-blocking TUN/TAP device.
 ///
@@ -18,15 +20,17 @@ use crate::error::Result;
 pub struct AsyncDevice(AsyncFd<Device>);
 impl AsyncDevice {
     fn new(name: impl AsRef<str>, mode: Mode, packet_info: bool) -> Result<Self> {
-        let (name, mut files, inet4_socket, inet6_socket) =
-            create_device(name, mode, 1, packet_info, true)?;
+        let (handle, mut files) = create_device(name, mode, 1, packet_info, true, false)?;
 
-        Ok(AsyncDevice(AsyncFd::new(Device {
-            name,
+        AsyncDevice::from_device(Device {
+            handle,
             file: files.pop().unwrap(),
-            inet4_socket,
-            inet6_socket,
-        })?))
+        })
+    }
+
+    // Registers a non-blocking device with the reactor of the runtime.
+    pub(crate) fn from_device(device: Device) -> Result<Self> {
+        Ok(AsyncDevice(AsyncFd::new(device)?))
     }
 
     /// Tries to read data from the device and fill the buffer `buf`.
@@ -37,7 +41,7 @@ impl AsyncDevice {
     /// # Returns
     /// * `Ok`: Containing the number of bytes read from the device.
     /// * `Err`: If the device was not ready to be read(a `WOULDBLOCK` err), or some other error
-    /// occurred.
+    ///   occurred.
     pub fn try_recv(&self, buf: &mut [u8]) -> Result<usize> {
         self.0.get_ref().recv(buf)
     }
@@ -50,7 +54,7 @@ impl AsyncDevice {
     /// # Returns
     /// * `Ok`: Containing the number of bytes written to the device.
     /// * `Err`: If the device was not ready to be written to(a `WOULDBLOCK` err), or some other error
-    /// occurred.
+    ///   occurred.
     pub fn try_send(&self, buf: &[u8]) -> Result<usize> {
         self.0.get_ref().send(buf)
     }
@@ -92,6 +96,187 @@ impl AsyncDevice {
             }
         }
     }
+
+    /// Tries to read a packet from the device into `buf`, after its reserved headroom.
+    ///
+    /// # Returns
+    /// * `Ok`: Containing the length of the packet in `buf`. If the packet was larger than the
+    ///   capacity of `buf`, it's truncated and [`PacketBuf::is_truncated`] returns `true`.
+    /// * `Err`: If the device was not ready to be read(a `WOULDBLOCK` err), or some other error
+    ///   occurred.
+    pub fn try_recv_buf(&self, buf: &mut PacketBuf) -> Result<usize> {
+        self.0.get_ref().recv_buf(buf)
+    }
+
+    /// Asyncronously reads a packet from the device into `buf`, after its reserved headroom.
+    ///
+    /// # Returns
+    /// * `Ok`: Containing the length of the packet in `buf`. If the packet was larger than the
+    ///   capacity of `buf`, it's truncated and [`PacketBuf::is_truncated`] returns `true`.
+    /// * `Err`: If reading data was unsuccessful.
+    pub async fn recv_buf(&self, buf: &mut PacketBuf) -> Result<usize> {
+        loop {
+            let mut guard = self.0.readable().await?;
+
+            match guard.try_io(|tun| Ok(tun.get_ref().recv_buf(buf)?)) {
+                Ok(result) => return Ok(result?),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Tries to read a packet from the device and scatter it into `bufs`.
+    pub fn try_recv_vectored(&self, bufs: &mut [io::IoSliceMut<'_>]) -> Result<usize> {
+        self.0.get_ref().recv_vectored(bufs)
+    }
+
+    /// Tries to write a packet gathered from `bufs` to the device.
+    pub fn try_send_vectored(&self, bufs: &[io::IoSlice<'_>]) -> Result<usize> {
+        self.0.get_ref().send_vectored(bufs)
+    }
+
+    /// Asyncronously reads a packet from the device and scatters it into `bufs`,
+    /// filling them in order.
+    pub async fn recv_vectored(&self, bufs: &mut [io::IoSliceMut<'_>]) -> Result<usize> {
+        loop {
+            let mut guard = self.0.readable().await?;
+
+            match guard.try_io(|tun| Ok(tun.get_ref().recv_vectored(bufs)?)) {
+                Ok(result) => return Ok(result?),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Asyncronously writes a packet gathered from `bufs` to the device.
+    pub async fn send_vectored(&self, bufs: &[io::IoSlice<'_>]) -> Result<usize> {
+        loop {
+            let mut guard = self.0.writable().await?;
+
+            match guard.try_io(|tun| Ok(tun.get_ref().send_vectored(bufs)?)) {
+                Ok(result) => return Ok(result?),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Tries to read the packets which are ready into `bufs`, one packet per buffer.
+    ///
+    /// # Returns
+    /// * `Ok`: Containing the number of filled buffers, which is at least one.
+    /// * `Err`: If no packet was ready to be read(a `WOULDBLOCK` err), or some other error
+    ///   occurred.
+    pub fn try_recv_batch(&self, bufs: &mut [PacketBuf]) -> Result<usize> {
+        self.0.get_ref().read_batch(bufs, false)
+    }
+
+    /// Asyncronously waits for a packet, then reads it and the other packets which are ready
+    /// into `bufs`, one packet per buffer.
+    ///
+    /// # Returns
+    /// * `Ok`: Containing the number of filled buffers, which is at least one.
+    /// * `Err`: If reading data was unsuccessful.
+    pub async fn recv_batch(&self, bufs: &mut [PacketBuf]) -> Result<usize> {
+        loop {
+            let mut guard = self.0.readable().await?;
+
+            match guard.try_io(|tun| Ok(tun.get_ref().read_batch(bufs, false)?)) {
+                Ok(result) => return Ok(result?),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+}
+
+impl AsyncDevice {
+    // Reads a packet into the unfilled part of `buf` once the device is readable, failing if
+    // it didn't fit. The kernel clamps the length it returns to the length of the buffer, so
+    // a truncated packet is only revealed by the spare byte `recv_whole` reads into.
+    pub(crate) fn poll_recv(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        loop {
+            let mut guard = futures::ready!(self.0.poll_read_ready(cx))?;
+
+            match guard.try_io(|inner| Ok(inner.get_ref().recv_whole(buf.initialize_unfilled())?)) {
+                Ok(result) => {
+                    buf.advance(result?);
+
+                    return Poll::Ready(Ok(()));
+                }
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    // Reads a packet into `buf` once the device is readable, failing if it didn't fit.
+    pub(crate) fn poll_recv_packet(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        loop {
+            let mut guard = futures::ready!(self.0.poll_read_ready(cx))?;
+
+            match guard.try_io(|inner| Ok(inner.get_ref().recv_whole(buf)?)) {
+                Ok(result) => return Poll::Ready(result),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    // Reads a packet into `buf` once the device is readable, failing if it didn't fit.
+    pub(crate) fn poll_recv_buf(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &mut PacketBuf,
+    ) -> Poll<Result<()>> {
+        buf.clear();
+
+        loop {
+            let mut guard = futures::ready!(self.0.poll_read_ready(cx))?;
+
+            match guard.try_io(|inner| Ok(inner.get_ref().recv_whole(buf.spare_mut())?)) {
+                Ok(result) => {
+                    let read = result.map_err(Error::from_io)?;
+                    buf.set_len(read);
+
+                    return Poll::Ready(Ok(()));
+                }
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    // Writes the packet gathered from `bufs` once the device is writable.
+    pub(crate) fn poll_send_vectored(
+        &self,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        loop {
+            let mut guard = futures::ready!(self.0.poll_write_ready(cx))?;
+
+            match guard.try_io(|inner| Ok(inner.get_ref().send_vectored(bufs)?)) {
+                Ok(result) => return Poll::Ready(result),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    // Writes the packet in `buf` once the device is writable.
+    pub(crate) fn poll_send(&self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        loop {
+            let mut guard = futures::ready!(self.0.poll_write_ready(cx))?;
+
+            match guard.try_io(|inner| (&inner.get_ref().file).write(buf)) {
+                Ok(result) => return Poll::Ready(result),
+                Err(_would_block) => continue,
+            }
+        }
+    }
 }
 
 impl ops::Deref for AsyncDevice {
@@ -107,27 +292,17 @@ impl ops::DerefMut for AsyncDevice {
     }
 }
 
+/// Reads a single packet per read, like the inherent methods. A packet which doesn't fit into
+/// the unfilled part of the buffer fails the read with
+/// [`Error::PacketTruncated`](crate::error::Error::PacketTruncated) instead of being silently
+/// truncated.
 impl AsyncRead for AsyncDevice {
     fn poll_read(
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &mut tokio::io::ReadBuf<'_>,
     ) -> Poll<io::Result<()>> {
-        let self_mut = self.get_mut();
-
-        loop {
-            let mut guard = futures::ready!(self_mut.0.poll_read_ready_mut(cx))?;
-
-            match guard.try_io(|inner| {
-                let read = inner.get_mut().read(buf.initialize_unfilled())?;
-                buf.advance(read);
-
-                Ok(read)
-            }) {
-                Ok(result) => return Poll::Ready(result.map(|_| ())),
-                Err(_would_block) => continue,
-            }
-        }
+        self.get_mut().poll_recv(cx, buf)
     }
 }
 
@@ -137,16 +312,19 @@ impl AsyncWrite for AsyncDevice {
         cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<io::Result<usize>> {
-        let self_mut = self.get_mut();
+        self.get_mut().poll_send(cx, buf)
+    }
 
-        loop {
-            let mut guard = futures::ready!(self_mut.0.poll_write_ready_mut(cx))?;
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().poll_send_vectored(cx, bufs)
+    }
 
-            match guard.try_io(|inner| inner.get_mut().write(buf)) {
-                Ok(result) => return Poll::Ready(result),
-                Err(_would_block) => continue,
-            }
-        }
+    fn is_write_vectored(&self) -> bool {
+        true
     }
 
     fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
@@ -158,6 +336,45 @@ impl AsyncWrite for AsyncDevice {
     }
 }
 
+/// Reads a single packet per read, like the inherent methods. A packet which doesn't fit into
+/// the buffer fails the read with [`Error::PacketTruncated`](crate::error::Error::PacketTruncated)
+/// instead of being silently truncated.
+impl futures::io::AsyncRead for AsyncDevice {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().poll_recv_packet(cx, buf)
+    }
+}
+
+impl futures::io::AsyncWrite for AsyncDevice {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().poll_send(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().poll_send_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
 /// Represents a non-blocking TUN device.
 #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
 #[derive(Debug)]
@@ -168,6 +385,11 @@ impl AsyncTun {
 
         Ok(AsyncTun(device))
     }
+
+    /// Splits the device into owned read and write halves, which can be moved into different tasks.
+    pub fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf) {
+        self.0.into_split()
+    }
 }
 impl ops::Deref for AsyncTun {
     type Target = AsyncDevice;
@@ -192,6 +414,11 @@ impl AsyncTap {
 
         Ok(AsyncTap(device))
     }
+
+    /// Splits the device into owned read and write halves, which can be moved into different tasks.
+    pub fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf) {
+        self.0.into_split()
+    }
 }
 impl ops::Deref for AsyncTap {
     type Target = AsyncDevice;
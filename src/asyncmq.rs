@@ -0,0 +1,183 @@
+use std::ops;
+use std::sync::Arc;
+
+use crate::asyncd::AsyncDevice;
+use crate::common::{create_device, Mode};
+use crate::device::Device;
+use crate::error::{Error, Result};
+use crate::multiq::{queue_count, QueueSlot};
+use crate::split::{OwnedReadHalf, OwnedWriteHalf};
+
+/// Represents a non-blocking queue of a multiqueue TUN/TAP device.
+///
+/// Contains the shared code between [`AsyncMQTun`](crate::AsyncMQTun) and
+/// [`AsyncMQTap`](crate::AsyncMQTap).
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[derive(Debug)]
+pub struct AsyncMQDevice {
+    // Closed before the slot is dropped, like the queues of an `MQDevice`.
+    device: AsyncDevice,
+    slot: Arc<QueueSlot>,
+}
+impl AsyncMQDevice {
+    fn new(
+        name: impl AsRef<str>,
+        mode: Mode,
+        device_count: usize,
+        packet_info: bool,
+    ) -> Result<impl Iterator<Item = Result<Self>>> {
+        if device_count == 0 {
+            return Err(Error::ZeroDevices);
+        }
+
+        let (handle, files) = create_device(name, mode, device_count, packet_info, true, true)?;
+        let slots = QueueSlot::new_all(&handle, device_count);
+
+        Ok(files.into_iter().zip(slots).map(move |(file, slot)| {
+            let device = AsyncDevice::from_device(Device {
+                handle: handle.clone(),
+                file,
+            })?;
+
+            Ok(AsyncMQDevice { device, slot })
+        }))
+    }
+
+    /// Attaches the multiqueue.
+    ///
+    /// # Returns
+    /// * `Ok`: If attaching was succesful.
+    /// * `Err(Error::SteeringNotRestored)`: If the queue was attached, but the steering
+    ///   settings of the queues couldn't be moved to their indices.
+    /// * `Err`: Otherwise.
+    pub fn attach(&self) -> Result<()> {
+        self.slot.attach(&self.device)
+    }
+
+    /// Detaches the multiqueue.
+    ///
+    /// # Returns
+    /// * `Ok`: If detaching was succesful.
+    /// * `Err(Error::SteeringNotRestored)`: If the queue was detached, but the steering
+    ///   settings of the queues couldn't be moved to their indices.
+    /// * `Err`: Otherwise.
+    pub fn detach(&self) -> Result<()> {
+        self.slot.detach(&self.device)
+    }
+
+    /// Opens another queue of the interface, e.g. for spawning a task for it as the load
+    /// increases.
+    ///
+    /// The new queue is attached right away. A queue is removed from the interface when it's
+    /// dropped.
+    pub fn add_queue(&self) -> Result<AsyncMQDevice> {
+        let (device, slot) = self.slot.add_queue(&self.device)?;
+
+        Ok(AsyncMQDevice {
+            device: AsyncDevice::from_device(device)?,
+            slot,
+        })
+    }
+
+    /// Returns the number of queues attached to the interface, which doesn't include the
+    /// detached queues.
+    pub fn queue_count(&self) -> Result<usize> {
+        queue_count(&self.device)
+    }
+
+    /// Returns the index of the queue in the interface, or `None` if it's detached.
+    ///
+    /// See [`MQDevice::queue_index`](crate::MQDevice::queue_index).
+    pub fn queue_index(&self) -> Option<usize> {
+        self.slot.index()
+    }
+
+    /// Splits the queue into owned read and write halves, which can be moved into different tasks.
+    pub fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf) {
+        self.device.into_split_with(Some(self.slot))
+    }
+}
+impl ops::Deref for AsyncMQDevice {
+    type Target = AsyncDevice;
+
+    fn deref(&self) -> &Self::Target {
+        &self.device
+    }
+}
+impl ops::DerefMut for AsyncMQDevice {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.device
+    }
+}
+
+/// Represents a non-blocking multiqueue TUN device.
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[derive(Debug)]
+pub struct AsyncMQTun(AsyncMQDevice);
+impl AsyncMQTun {
+    pub fn new(name: impl AsRef<str>, device_count: usize, packet_info: bool) -> Result<Vec<Self>> {
+        let devices = AsyncMQDevice::new(name, Mode::Tun, device_count, packet_info)?;
+
+        devices.map(|device| Ok(AsyncMQTun(device?))).collect()
+    }
+
+    /// Opens another queue of the interface.
+    ///
+    /// See [`AsyncMQDevice::add_queue`].
+    pub fn add_queue(&self) -> Result<Self> {
+        self.0.add_queue().map(AsyncMQTun)
+    }
+
+    /// Splits the queue into owned read and write halves, which can be moved into different tasks.
+    pub fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf) {
+        self.0.into_split()
+    }
+}
+impl ops::Deref for AsyncMQTun {
+    type Target = AsyncMQDevice;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl ops::DerefMut for AsyncMQTun {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// Represents a non-blocking multiqueue TAP device.
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[derive(Debug)]
+pub struct AsyncMQTap(AsyncMQDevice);
+impl AsyncMQTap {
+    pub fn new(name: impl AsRef<str>, device_count: usize, packet_info: bool) -> Result<Vec<Self>> {
+        let devices = AsyncMQDevice::new(name, Mode::Tap, device_count, packet_info)?;
+
+        devices.map(|device| Ok(AsyncMQTap(device?))).collect()
+    }
+
+    /// Opens another queue of the interface.
+    ///
+    /// See [`AsyncMQDevice::add_queue`].
+    pub fn add_queue(&self) -> Result<Self> {
+        self.0.add_queue().map(AsyncMQTap)
+    }
+
+    /// Splits the queue into owned read and write halves, which can be moved into different tasks.
+    pub fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf) {
+        self.0.into_split()
+    }
+}
+impl ops::Deref for AsyncMQTap {
+    type Target = AsyncMQDevice;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl ops::DerefMut for AsyncMQTap {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
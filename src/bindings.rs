@@ -11,3 +11,96 @@ pub struct in6_ifreq {
     pub ifr6_prefixlen: u32,
     pub ifr6_ifindex: i32,
 }
+
+// The structs below are part of the rtnetlink uapi (`linux/rtnetlink.h`).
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct nlmsghdr {
+    pub nlmsg_len: u32,
+    pub nlmsg_type: u16,
+    pub nlmsg_flags: u16,
+    pub nlmsg_seq: u32,
+    pub nlmsg_pid: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct rtattr {
+    pub rta_len: u16,
+    pub rta_type: u16,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct rtmsg {
+    pub rtm_family: u8,
+    pub rtm_dst_len: u8,
+    pub rtm_src_len: u8,
+    pub rtm_tos: u8,
+    pub rtm_table: u8,
+    pub rtm_protocol: u8,
+    pub rtm_scope: u8,
+    pub rtm_type: u8,
+    pub rtm_flags: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct ifinfomsg {
+    pub ifi_family: u8,
+    pub __ifi_pad: u8,
+    pub ifi_type: u16,
+    pub ifi_index: i32,
+    pub ifi_flags: u32,
+    pub ifi_change: u32,
+}
+
+// The structs below are part of the fib rules uapi (`linux/fib_rules.h`).
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct fib_rule_hdr {
+    pub family: u8,
+    pub dst_len: u8,
+    pub src_len: u8,
+    pub tos: u8,
+    pub table: u8,
+    pub res1: u8,
+    pub res2: u8,
+    pub action: u8,
+    pub flags: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct fib_rule_uid_range {
+    pub start: u32,
+    pub end: u32,
+}
+
+// The struct below is part of the neighbor uapi (`linux/neighbour.h`).
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct ndmsg {
+    pub ndm_family: u8,
+    pub ndm_pad1: u8,
+    pub ndm_pad2: u16,
+    pub ndm_ifindex: i32,
+    pub ndm_state: u16,
+    pub ndm_flags: u8,
+    pub ndm_type: u8,
+}
+
+// The struct below is part of the address uapi (`linux/if_addr.h`).
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct ifaddrmsg {
+    pub ifa_family: u8,
+    pub ifa_prefixlen: u8,
+    pub ifa_flags: u8,
+    pub ifa_scope: u8,
+    pub ifa_index: u32,
+}
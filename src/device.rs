@@ -1,326 +1,53 @@
 use std::os::unix::prelude::*;
-use std::str::FromStr;
-use std::sync::Arc;
-use std::{fs, io, net, ops};
+use std::time::{Duration, Instant};
+use std::{fs, io, ops};
+
+use nix::errno::Errno;
+use nix::fcntl::{fcntl, FcntlArg, OFlag};
+use nix::poll::{PollFd, PollFlags};
+use nix::sys::uio;
 
 use crate::common::create_device;
-use crate::error::Result;
-use crate::flags::Flags;
-use crate::{bindings, ioctl, sockaddr, Mode};
+use crate::error::{Context, Error, Result};
+use crate::flags::TunFlags;
+use crate::list::find_interface;
+use crate::{ioctl, InterfaceHandle, Mode, PacketBuf};
+
+// Length of an Ethernet header carrying a VLAN tag.
+const ETHERNET_HEADER_LEN: usize = 18;
+
+// Length of `struct tun_pi`. Source: `linux/if_tun.h`.
+const PACKET_INFO_LEN: usize = 4;
 
 /// Represents a blocking TUN/TAP device.
 ///
 /// Contains the shared code between [`Tun`](crate::Tun) and [`Tap`](crate::Tap).
 #[derive(Debug)]
 pub struct Device {
-    pub(crate) name: Arc<[i8; 16]>,
+    pub(crate) handle: InterfaceHandle,
     pub(crate) file: fs::File,
-
-    pub(crate) inet4_socket: Arc<OwnedFd>,
-    pub(crate) inet6_socket: Arc<OwnedFd>,
 }
 
 impl Device {
-    fn new(name: impl AsRef<str>, mode: Mode, packet_info: bool) -> Result<Self> {
-        let (name, mut files, inet4_socket, inet6_socket) =
-            create_device(name, mode, 1, packet_info, false)?;
+    fn new(
+        name: impl AsRef<str>,
+        mode: Mode,
+        packet_info: bool,
+        non_blocking: bool,
+    ) -> Result<Self> {
+        let (handle, mut files) = create_device(name, mode, 1, packet_info, non_blocking, false)?;
 
         Ok(Self {
-            name,
+            handle,
             file: files.pop().unwrap(),
-            inet4_socket,
-            inet6_socket,
         })
     }
 
-    /// Returns The name of the device chosen by the kernel.
-    #[rustfmt::skip]
-    pub fn name(&self) -> String {
-        String::from_iter(self.name.iter().map_while(|c| {
-            let c = *c as u8 as char;
-
-            if c != '\0' { Some(c) } else { None }
-        }))
-    }
-
-    /// Returns the active flags of the interface.
-    pub fn flags(&self) -> Result<Flags> {
-        self.read_flags()?.try_into()
-    }
-
-    /// Brings the device up which makes it ready to send and receive packets.
-    pub fn bring_up(&self) -> Result<()> {
-        self.add_flags(nix::libc::IFF_UP | nix::libc::IFF_RUNNING)
-    }
-
-    /// Brings the device down which makes it unable to send and receive packets.
-    pub fn bring_down(&self) -> Result<()> {
-        self.del_flags(nix::libc::IFF_UP | nix::libc::IFF_RUNNING)
-    }
-
-    /// Sets the MTU of the device.
-    pub fn set_mtu(&self, mtu: i32) -> Result<()> {
-        let mut ifreq = self.new_ifreq();
-
-        ifreq.ifr_ifru.ifru_mtu = mtu;
-
-        unsafe {
-            ioctl::siocsifmtu(
-                self.inet4_socket.as_raw_fd(),
-                &ifreq as *const bindings::ifreq,
-            )?
-        };
-
-        Ok(())
-    }
-
-    /// Returns the MTU of the device.
-    pub fn get_mtu(&self) -> Result<i32> {
-        let mut ifreq = self.new_ifreq();
-
-        unsafe {
-            ioctl::siocgifmtu(
-                self.inet4_socket.as_raw_fd(),
-                &mut ifreq as *mut bindings::ifreq,
-            )?
-        };
-
-        // Safety:
-        //
-        // Since we issued an ioctl for getting the MTU, it's safe to assume
-        // that if the ioctl was successfull, kernel had set the `ifru_mtu` variant.
-        Ok(unsafe { ifreq.ifr_ifru.ifru_mtu })
-    }
-
-    /// Sets the netmask of the device.
-    pub fn set_netmask(&self, netmask: net::Ipv4Addr) -> Result<()> {
-        let mut ifreq = self.new_ifreq();
-
-        ifreq.ifr_ifru.ifru_netmask = sockaddr::to_sockaddr(netmask);
-
-        unsafe {
-            ioctl::siocsifnetmask(
-                self.inet4_socket.as_raw_fd(),
-                &ifreq as *const bindings::ifreq,
-            )?
-        };
-
-        Ok(())
-    }
-
-    /// Sets the netmask of the device.
-    pub fn get_netmask(&self) -> Result<net::Ipv4Addr> {
-        let mut ifreq = self.new_ifreq();
-
-        unsafe {
-            ioctl::siocgifnetmask(
-                self.inet4_socket.as_raw_fd(),
-                &mut ifreq as *mut bindings::ifreq,
-            )?
-        };
-
-        // Safety:
-        //
-        // Since we issued an ioctl for getting the netmask, it's safe to assume
-        // that if the ioctl was successfull, kernel had set the `ifru_netmask` variant.
-        Ok(sockaddr::to_ipv4(unsafe { ifreq.ifr_ifru.ifru_netmask }))
-    }
-
-    /// Returns the index of the interface.
-    pub fn get_index(&self) -> Result<i32> {
-        let mut ifreq = self.new_ifreq();
-
-        unsafe {
-            ioctl::siocgifindex(
-                self.inet4_socket.as_raw_fd(),
-                &mut ifreq as *mut bindings::ifreq,
-            )?
-        };
-
-        // Safety:
-        //
-        // Since we issued an ioctl for getting the index, it's safe to assume
-        // that if the ioctl was successfull, kernel had set the `ifru_ivalue` variant.
-        Ok(unsafe { ifreq.ifr_ifru.ifru_ivalue })
-    }
-
-    /// Adds the specified `addr` to the list of IPv6 addresses of the interface.
-    pub fn set_ipv6_addr(&self, addr: net::Ipv6Addr) -> Result<()> {
-        let ifindex = self.get_index()?;
-
-        #[rustfmt::skip]
-        let in6_ifreq = bindings::in6_ifreq {
-            ifr6_addr: nix::libc::in6_addr { s6_addr: addr.octets() },
-            ifr6_prefixlen: 64,
-            ifr6_ifindex: ifindex,
-        };
-
-        unsafe {
-            ioctl::siocsifaddr6(
-                self.inet6_socket.as_raw_fd(),
-                &in6_ifreq as *const bindings::in6_ifreq,
-            )?;
-        }
-
-        Ok(())
-    }
-
-    /// Returns the list of IPv6 addresses of the interface.
-    pub fn get_ipv6_addrs(&self) -> Result<Vec<net::Ipv6Addr>> {
-        // `getifaddrs` returns all addresses of all interfaces in the system.
-        Ok(nix::ifaddrs::getifaddrs()?
-            // We are only interested in the addresses related to our interface.
-            .filter(|iface| iface.interface_name == self.name())
-            // Among the addresses related to our interface, we want the IPv6 ones.
-            .filter_map(|iface| {
-                iface
-                    .address
-                    .and_then(|addr| addr.as_sockaddr_in6().map(|in6_addr| in6_addr.ip()))
-                //                   ----------------------                -------------
-                //                     try to convert the                   extract the
-                //                     address to IPv6                      ip from IPv6
-            })
-            .collect())
-    }
-
-    /// Deletes the specified IPv6 address from the interface.
-    pub fn del_ipv6_addr(&self, addr: net::Ipv6Addr) -> Result<()> {
-        let ifindex = self.get_index()?;
-
-        #[rustfmt::skip]
-        let in6_ifreq = bindings::in6_ifreq {
-            ifr6_addr: nix::libc::in6_addr { s6_addr: addr.octets() },
-            ifr6_prefixlen: 64,
-            ifr6_ifindex: ifindex,
-        };
-
-        unsafe {
-            ioctl::siocdifaddr6(
-                self.inet6_socket.as_raw_fd(),
-                &in6_ifreq as *const bindings::in6_ifreq,
-            )?;
-        }
-
-        Ok(())
-    }
-
-    /// Sets the IPv4 address of the device.
-    pub fn set_addr(&self, addr: net::Ipv4Addr) -> Result<()> {
-        let mut ifreq = self.new_ifreq();
-
-        ifreq.ifr_ifru.ifru_addr = sockaddr::to_sockaddr(addr);
-
-        unsafe {
-            ioctl::siocsifaddr(
-                self.inet4_socket.as_raw_fd(),
-                &ifreq as *const bindings::ifreq,
-            )?
-        };
-
-        Ok(())
-    }
-
-    /// Returns the IPv4 address of the device.
-    pub fn get_addr(&self) -> Result<net::Ipv4Addr> {
-        let mut ifreq = self.new_ifreq();
-
-        unsafe {
-            ioctl::siocgifaddr(
-                self.inet4_socket.as_raw_fd(),
-                &mut ifreq as *mut bindings::ifreq,
-            )?
-        };
-
-        // Safety:
-        //
-        // Since we issued a ioctl for getting the netmask, it's safe to assume
-        // that if the ioctl was successfull, kernel had set the `ifru_netmask` variant.
-        Ok(sockaddr::to_ipv4(unsafe { ifreq.ifr_ifru.ifru_addr }))
-    }
-
-    /// Deletes the IPv4 address of the interface.
-    pub fn del_addr(&self) -> Result<()> {
-        let mut ifreq = self.new_ifreq();
-
-        ifreq.ifr_ifru.ifru_addr =
-            sockaddr::to_sockaddr(net::Ipv4Addr::from_str("0.0.0.0").unwrap());
-
-        unsafe {
-            ioctl::siocsifaddr(
-                self.inet4_socket.as_raw_fd(),
-                &ifreq as *const bindings::ifreq,
-            )?
-        };
-
-        Ok(())
-    }
-
-    /// Sets the broadcast IPv4 address of the device.
-    pub fn set_brd_addr(&self, addr: net::Ipv4Addr) -> Result<()> {
-        let mut ifreq = self.new_ifreq();
-
-        ifreq.ifr_ifru.ifru_broadaddr = sockaddr::to_sockaddr(addr);
-
-        unsafe {
-            ioctl::siocsifbrdaddr(
-                self.inet4_socket.as_raw_fd(),
-                &ifreq as *const bindings::ifreq,
-            )?
-        };
-
-        Ok(())
-    }
-
-    /// Returns the broadcast IPv4 address of the device.
-    pub fn get_brd_addr(&self) -> Result<net::Ipv4Addr> {
-        let mut ifreq = self.new_ifreq();
-
-        unsafe {
-            ioctl::siocgifbrdaddr(
-                self.inet4_socket.as_raw_fd(),
-                &mut ifreq as *mut bindings::ifreq,
-            )?
-        };
-
-        // Safety:
-        //
-        // Since we issued a ioctl for getting the broadcast address, it's safe to assume
-        // that if the ioctl was successfull, kernel had set the `ifru_broadaddr` variant.
-        Ok(sockaddr::to_ipv4(unsafe { ifreq.ifr_ifru.ifru_broadaddr }))
-    }
-
-    /// Sets the destination IPv4 address of the device.
-    pub fn set_dst_addr(&self, addr: net::Ipv4Addr) -> Result<()> {
-        let mut ifreq = self.new_ifreq();
-
-        ifreq.ifr_ifru.ifru_dstaddr = sockaddr::to_sockaddr(addr);
-
-        unsafe {
-            ioctl::siocsifdstaddr(
-                self.inet4_socket.as_raw_fd(),
-                &ifreq as *const bindings::ifreq,
-            )?
-        };
-
-        Ok(())
-    }
-
-    /// Returns the destination IPv4 address of the device.
-    pub fn get_dst_addr(&self) -> Result<net::Ipv4Addr> {
-        let mut ifreq = self.new_ifreq();
-
-        unsafe {
-            ioctl::siocgifdstaddr(
-                self.inet4_socket.as_raw_fd(),
-                &mut ifreq as *mut bindings::ifreq,
-            )?
-        };
-
-        // Safety:
-        //
-        // Since we issued a ioctl for getting the destination address, it's safe to assume
-        // that if the ioctl was successfull, kernel had set the `ifru_dstaddr` variant.
-        Ok(sockaddr::to_ipv4(unsafe { ifreq.ifr_ifru.ifru_dstaddr }))
+    /// Returns a handle for configuring the interface of the device.
+    ///
+    /// The configuration methods of the handle can also be called on the device directly.
+    pub fn handle(&self) -> &InterfaceHandle {
+        &self.handle
     }
 
     //    /// Sets the owner of the device.
@@ -350,84 +77,283 @@ impl Device {
     //
     //        Ok(())
     //    }
-    //
-    //    /// Can be used to make the TUN/TAP interface persistent. In this mode,
-    //    /// the interface won't be destroyed when the last process closes the associated `/dev/net/tun` file descriptor.
-    //    ///
-    //    /// # Returns
-    //    /// * `Ok`: If the device changed to be persistent.
-    //    /// * `Err`: If the ioctl failed.
-    //    pub fn persist(&self, persist: bool) -> Result<()> {
-    //        unsafe { ioctl::tunsetpersist(self.file.as_raw_fd(), if persist { 1 } else { 0 })? };
-    //
-    //        Ok(())
-    //    }
 
-    // Returns an empty ifreq with the same name of this device.
-    fn new_ifreq(&self) -> bindings::ifreq {
-        let mut ifreq: bindings::ifreq = unsafe { std::mem::zeroed() };
+    /// Can be used to make the TUN/TAP interface persistent. In this mode,
+    /// the interface won't be destroyed when the last process closes the associated `/dev/net/tun` file descriptor.
+    ///
+    /// A persistent interface can be destroyed using [`delete_device`](crate::delete_device).
+    ///
+    /// # Returns
+    /// * `Ok`: If the device changed to be persistent.
+    /// * `Err`: If the ioctl failed.
+    pub fn persist(&self, persist: bool) -> Result<()> {
+        unsafe { ioctl::tunsetpersist(self.file.as_raw_fd(), persist.into()) }
+            .context("TUNSETPERSIST", &self.name)?;
 
-        ifreq.ifr_ifrn.ifrn_name = *self.name;
+        Ok(())
+    }
 
-        ifreq
+    /// Creates a new device which shares the same queue with this one, by duplicating the
+    /// file descriptor of the device.
+    ///
+    /// Can be used for reading from and writing to the device on different threads.
+    pub fn try_clone(&self) -> Result<Self> {
+        Ok(Device {
+            handle: self.handle.clone(),
+            file: self.file.try_clone().context("dup", &self.name)?,
+        })
     }
 
-    // Returns the active flags of the device.
-    fn read_flags(&self) -> Result<i32> {
-        let mut ifreq = self.new_ifreq();
+    /// Writes the data in `buf` into the device.
+    pub fn send(&self, buf: &[u8]) -> Result<usize> {
+        nix::unistd::write(self.file.as_raw_fd(), buf).context("write", &self.name)
+    }
 
-        unsafe {
-            ioctl::siocgifflags(
-                self.inet4_socket.as_raw_fd(),
-                &mut ifreq as *mut bindings::ifreq,
-            )?
-        };
+    /// Reads the data from device into `buf`.
+    pub fn recv(&self, buf: &mut [u8]) -> Result<usize> {
+        nix::unistd::read(self.file.as_raw_fd(), buf).context("read", &self.name)
+    }
+
+    /// Reads a packet from the device into `buf`, after its reserved headroom.
+    ///
+    /// A [`PacketBuf`] can be written into the device using [`send`](Self::send), since it
+    /// dereferences to the packet.
+    ///
+    /// # Returns
+    /// * `Ok`: Containing the length of the packet in `buf`. If the packet was larger than the
+    ///   capacity of `buf`, it's truncated and [`PacketBuf::is_truncated`] returns `true`.
+    /// * `Err`: If reading data was unsuccessful.
+    pub fn recv_buf(&self, buf: &mut PacketBuf) -> Result<usize> {
+        buf.clear();
+
+        let capacity = buf.capacity();
+        // A read into the spare byte tells a truncated packet apart from one which fills `buf`.
+        let mut spare = [0; 1];
+        let read = self.recv_vectored(&mut [
+            io::IoSliceMut::new(buf.spare_mut()),
+            io::IoSliceMut::new(&mut spare),
+        ])?;
+
+        buf.set_len(read.min(capacity));
+        buf.set_truncated(read > capacity);
+
+        Ok(buf.len())
+    }
+
+    // Reads a packet into `buf`, failing if the packet didn't fit. The spare byte after `buf`
+    // tells a truncated packet apart from one which fills `buf` exactly.
+    #[cfg(any(feature = "tokio", feature = "async-io"))]
+    pub(crate) fn recv_whole(&self, buf: &mut [u8]) -> Result<usize> {
+        let mut spare = [0; 1];
+        let read =
+            self.recv_vectored(&mut [io::IoSliceMut::new(buf), io::IoSliceMut::new(&mut spare)])?;
+
+        if read > buf.len() {
+            return Err(Error::PacketTruncated(buf.len()));
+        }
 
-        Ok(unsafe { ifreq.ifr_ifru.ifru_flags.into() })
+        Ok(read)
     }
 
-    fn add_flags(&self, flags: i32) -> Result<()> {
-        let mut ifreq = self.new_ifreq();
+    /// Returns the size of a buffer which can hold any packet read from the device.
+    ///
+    /// It's the MTU of the interface plus the headers prepended by the device, i.e. the
+    /// Ethernet header of a TAP device, the packet info, and the virtio-net header.
+    /// Packets coalesced using GSO, which are only read from a device with
+    /// [`IFF_VNET_HDR`](crate::flags::TunFlags::IFF_VNET_HDR), can be larger.
+    pub fn buffer_size(&self) -> Result<usize> {
+        // The flags reported by `TUNGETIFF` can't be used, since `IFF_NOFILTER` overlaps `IFF_NO_PI`.
+        let interface = find_interface(&self.current_name()?)?;
+        let mut size = self.get_mtu()? as usize;
 
-        ifreq.ifr_ifru.ifru_flags = self.read_flags()? as i16;
+        if interface.mode == Mode::Tap {
+            size += ETHERNET_HEADER_LEN;
+        }
 
-        unsafe {
-            ifreq.ifr_ifru.ifru_flags |= flags as i16;
+        if !interface.flags.contains(TunFlags::IFF_NO_PI) {
+            size += PACKET_INFO_LEN;
+        }
 
-            ioctl::siocsifflags(
-                self.inet4_socket.as_raw_fd(),
-                &ifreq as *const bindings::ifreq,
-            )?;
+        if interface.flags.contains(TunFlags::IFF_VNET_HDR) {
+            let mut vnet_hdr_len = 0;
+
+            unsafe { ioctl::tungetvnethdrsz(self.file.as_raw_fd(), &mut vnet_hdr_len) }
+                .context("TUNGETVNETHDRSZ", &self.name)?;
+
+            size += vnet_hdr_len as usize;
         }
 
-        Ok(())
+        Ok(size)
     }
 
-    fn del_flags(&self, flags: i32) -> Result<()> {
-        let mut ifreq = self.new_ifreq();
+    /// Writes a packet gathered from `bufs` into the device.
+    ///
+    /// Can be used for writing the headers and the payload of a packet from different
+    /// buffers without copying them into one buffer first.
+    pub fn send_vectored(&self, bufs: &[io::IoSlice<'_>]) -> Result<usize> {
+        uio::writev(self.file.as_raw_fd(), bufs).context("writev", &self.name)
+    }
 
-        ifreq.ifr_ifru.ifru_flags = self.read_flags()? as i16;
+    /// Reads a packet from the device and scatters it into `bufs`, filling them in order.
+    pub fn recv_vectored(&self, bufs: &mut [io::IoSliceMut<'_>]) -> Result<usize> {
+        uio::readv(self.file.as_raw_fd(), bufs).context("readv", &self.name)
+    }
 
-        unsafe {
-            ifreq.ifr_ifru.ifru_flags &= !(flags) as i16;
+    /// Reads up to `bufs.len()` packets from the device, one packet per buffer.
+    ///
+    /// Blocks until the first packet is available. After that, only the packets which are
+    /// already queued are read.
+    ///
+    /// Packets larger than a buffer are truncated, which is reported by
+    /// [`PacketBuf::is_truncated`].
+    ///
+    /// # Returns
+    /// * `Ok`: Containing the number of filled buffers, which is at least one.
+    /// * `Err`: If reading the first packet was unsuccessful.
+    pub fn recv_batch(&self, bufs: &mut [PacketBuf]) -> Result<usize> {
+        self.read_batch(bufs, true)
+    }
 
-            ioctl::siocsifflags(
-                self.inet4_socket.as_raw_fd(),
-                &ifreq as *const bindings::ifreq,
-            )?;
+    // Reads packets into `bufs` until they are all filled, or no packet is ready.
+    //
+    // If `check_ready` is set, the readiness of the device is checked before each read except
+    // the first one. Otherwise, the device must be non-blocking.
+    pub(crate) fn read_batch(&self, bufs: &mut [PacketBuf], check_ready: bool) -> Result<usize> {
+        for (i, buf) in bufs.iter_mut().enumerate() {
+            if i > 0 && check_ready && !self.wait(PollFlags::POLLIN, Duration::ZERO)? {
+                return Ok(i);
+            }
+
+            match self.recv_buf(buf) {
+                Ok(_) => {}
+                Err(err) if i > 0 && err.errno() == Some(Errno::EAGAIN) => return Ok(i),
+                Err(err) => return Err(err),
+            }
         }
 
-        Ok(())
+        Ok(bufs.len())
+    }
+
+    // Calls `op` once any of `events` happens, waiting at most `timeout` for it. The wait is
+    // restarted if `op` fails with `EAGAIN`, i.e. another reader or writer of a non-blocking
+    // device got ahead of us.
+    pub(crate) fn with_timeout<R>(
+        &self,
+        events: PollFlags,
+        timeout: Duration,
+        mut op: impl FnMut() -> Result<R>,
+    ) -> Result<R> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if !self.wait(events, remaining)? {
+                return Err(Error::TimedOut);
+            }
+
+            match op() {
+                Err(err) if err.errno() == Some(Errno::EAGAIN) => continue,
+                result => return result,
+            }
+        }
     }
 
-    /// Writes the data in `buf` into the device.
-    pub fn send(&self, buf: &[u8]) -> Result<usize> {
-        Ok(nix::unistd::write(self.file.as_raw_fd(), buf)?)
+    // Waits at most `timeout` for any of `events` to happen. Returns `false` if none happened.
+    //
+    // An error condition of the device also ends the wait, so it's reported by the next read
+    // or write.
+    fn wait(&self, events: PollFlags, timeout: Duration) -> Result<bool> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            // Round up, so the wait doesn't end right before the deadline.
+            let millis = remaining.as_nanos().div_ceil(1_000_000);
+
+            let mut fds = [PollFd::new(self.file.as_raw_fd(), events)];
+            match nix::poll::poll(&mut fds, millis.min(i32::MAX as u128) as i32) {
+                Ok(ready) => return Ok(ready > 0),
+                Err(Errno::EINTR) => continue,
+                Err(errno) => return Err(errno).context("poll", &self.name),
+            }
+        }
     }
+}
 
-    /// Reads the data from device into `buf`.
-    pub fn recv(&self, buf: &mut [u8]) -> Result<usize> {
-        Ok(nix::unistd::read(self.file.as_raw_fd(), buf)?)
+// Implements the methods which wait for the device, or change whether it blocks, on the
+// blocking device types. They're left out of `Device`, which the async devices dereference to,
+// since they'd block the executor, or break the reactor.
+macro_rules! impl_blocking {
+    ($ty:ty) => {
+        impl $ty {
+            /// Reads a packet from the device into `buf`, waiting at most `timeout` for it.
+            ///
+            /// # Returns
+            /// * `Ok`: Containing the number of bytes read from the device.
+            /// * `Err`: Containing [`Error::TimedOut`] if no packet arrived in time, or some other
+            ///   error if reading data was unsuccessful.
+            ///
+            /// **NOTE**: The device is polled before it's read. If another reader, e.g. a clone of
+            /// the device, takes the packet in between, a blocking device blocks until the next
+            /// packet regardless of `timeout`. A non-blocking device waits for the next packet
+            /// until the deadline instead, see [`set_nonblocking`](Self::set_nonblocking).
+            pub fn recv_timeout(&self, buf: &mut [u8], timeout: Duration) -> Result<usize> {
+                self.with_timeout(PollFlags::POLLIN, timeout, || self.recv(buf))
+            }
+
+            /// Writes the data in `buf` into the device, waiting at most `timeout` for the device
+            /// to become writable.
+            ///
+            /// # Returns
+            /// * `Ok`: Containing the number of bytes written to the device.
+            /// * `Err`: Containing [`Error::TimedOut`] if the device didn't become writable in
+            ///   time, or some other error if writing data was unsuccessful.
+            ///
+            /// **NOTE**: Like [`recv_timeout`](Self::recv_timeout), a blocking device might block
+            /// past the deadline if another writer fills the device after it's polled.
+            pub fn send_timeout(&self, buf: &[u8], timeout: Duration) -> Result<usize> {
+                self.with_timeout(PollFlags::POLLOUT, timeout, || self.send(buf))
+            }
+
+            /// Returns whether a packet can be read from the device without blocking.
+            pub fn readable(&self) -> Result<bool> {
+                self.wait(PollFlags::POLLIN, Duration::ZERO)
+            }
+
+            /// Returns whether a packet can be written into the device without blocking.
+            pub fn writable(&self) -> Result<bool> {
+                self.wait(PollFlags::POLLOUT, Duration::ZERO)
+            }
+
+            /// Moves the device into or out of non-blocking mode.
+            ///
+            /// In non-blocking mode, reading from and writing to the device fail with `EAGAIN`
+            /// instead of blocking. The mode is shared with the clones of the device.
+            pub fn set_nonblocking(&self, non_blocking: bool) -> Result<()> {
+                let flags =
+                    fcntl(self.file.as_raw_fd(), FcntlArg::F_GETFL).context("fcntl", &self.name)?;
+
+                let mut flags = OFlag::from_bits_truncate(flags);
+                flags.set(OFlag::O_NONBLOCK, non_blocking);
+
+                fcntl(self.file.as_raw_fd(), FcntlArg::F_SETFL(flags))
+                    .context("fcntl", &self.name)?;
+
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_blocking!(Tun);
+impl_blocking!(Tap);
+impl_blocking!(crate::MQDevice);
+
+impl ops::Deref for Device {
+    type Target = InterfaceHandle;
+
+    fn deref(&self) -> &Self::Target {
+        &self.handle
     }
 }
 
@@ -453,12 +379,28 @@ impl AsRawFd for Device {
     }
 }
 
+impl AsFd for Device {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.file.as_fd()
+    }
+}
+
 /// Represents a blocking TUN device.
 #[derive(Debug)]
 pub struct Tun(Device);
 impl Tun {
     pub fn new(name: impl AsRef<str>, packet_info: bool) -> Result<Self> {
-        let device = Device::new(name, Mode::Tun, packet_info)?;
+        let device = Device::new(name, Mode::Tun, packet_info, false)?;
+
+        Ok(Tun(device))
+    }
+
+    /// Creates a TUN device whose file descriptor is non-blocking, e.g. for registering it
+    /// with an event loop.
+    ///
+    /// Reading from and writing to the device fail with `EAGAIN` instead of blocking.
+    pub fn new_nonblocking(name: impl AsRef<str>, packet_info: bool) -> Result<Self> {
+        let device = Device::new(name, Mode::Tun, packet_info, true)?;
 
         Ok(Tun(device))
     }
@@ -481,7 +423,17 @@ impl ops::DerefMut for Tun {
 pub struct Tap(Device);
 impl Tap {
     pub fn new(name: impl AsRef<str>, packet_info: bool) -> Result<Self> {
-        let device = Device::new(name, Mode::Tap, packet_info)?;
+        let device = Device::new(name, Mode::Tap, packet_info, false)?;
+
+        Ok(Tap(device))
+    }
+
+    /// Creates a TAP device whose file descriptor is non-blocking, e.g. for registering it
+    /// with an event loop.
+    ///
+    /// Reading from and writing to the device fail with `EAGAIN` instead of blocking.
+    pub fn new_nonblocking(name: impl AsRef<str>, packet_info: bool) -> Result<Self> {
+        let device = Device::new(name, Mode::Tap, packet_info, true)?;
 
         Ok(Tap(device))
     }
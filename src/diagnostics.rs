@@ -0,0 +1,314 @@
+//! Diagnostics of the environment for creating TUN/TAP devices.
+//!
+//! Creating a device can fail for reasons that are hard to tell apart from the returned errno
+//! alone, e.g. an `EPERM` can be caused by a missing capability or by a user namespace.
+//! [`diagnose`] checks the usual suspects and explains how to fix them.
+
+use std::{fmt, fs};
+
+use nix::errno::Errno;
+use nix::unistd::{access, AccessFlags};
+
+use crate::error::Error;
+use crate::list::find_interface;
+use crate::InterfaceName;
+
+// Index of `CAP_NET_ADMIN` in the capability sets. Source: `linux/capability.h`.
+const CAP_NET_ADMIN: u32 = 12;
+
+/// The aspect of the environment a [`Check`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CheckKind {
+    /// Whether `/dev/net/tun` exists and is accessible.
+    TunDevice,
+
+    /// Whether the TUN/TAP driver is loaded into the kernel.
+    TunModule,
+
+    /// Whether the process has `CAP_NET_ADMIN` in its effective capability set.
+    NetAdminCapability,
+
+    /// Whether the process runs inside a user namespace, which limits what its capabilities allow.
+    UserNamespace,
+
+    /// Whether the requested interface name can be used for a TUN/TAP device.
+    InterfaceName,
+}
+
+/// Outcome of a [`Check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Nothing to do.
+    Ok,
+
+    /// Creating a device might fail, depending on the rest of the environment.
+    Warning,
+
+    /// Creating a device will fail.
+    Error,
+}
+
+/// Result of checking a single aspect of the environment.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Check {
+    /// What was checked.
+    pub kind: CheckKind,
+
+    /// Outcome of the check.
+    pub severity: Severity,
+
+    /// What was found and, if something is wrong, how to fix it.
+    pub message: String,
+}
+
+/// Results of all the checks performed by [`diagnose`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Report {
+    /// The performed checks, in the order they were performed.
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    /// Returns whether none of the checks found an error.
+    pub fn is_ok(&self) -> bool {
+        self.severity() != Severity::Error
+    }
+
+    /// Returns the worst outcome among the checks.
+    pub fn severity(&self) -> Severity {
+        self.checks
+            .iter()
+            .map(|check| check.severity)
+            .max()
+            .unwrap_or(Severity::Ok)
+    }
+
+    /// Returns the checks which found a problem.
+    pub fn problems(&self) -> impl Iterator<Item = &Check> {
+        self.checks
+            .iter()
+            .filter(|check| check.severity != Severity::Ok)
+    }
+
+    /// Returns the check of `kind`, if it was performed.
+    pub fn get(&self, kind: CheckKind) -> Option<&Check> {
+        self.checks.iter().find(|check| check.kind == kind)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            let severity = match check.severity {
+                Severity::Ok => "ok",
+                Severity::Warning => "warning",
+                Severity::Error => "error",
+            };
+
+            writeln!(f, "[{}] {:?}: {}", severity, check.kind, check.message)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks whether a TUN/TAP device can be created in the current environment.
+///
+/// If `name` is given, it's also checked whether a device with that name can be created.
+///
+/// The checks don't change the system, so it's safe to call this function in order to
+/// explain an error returned by [`Tun::new`](crate::Tun::new) and friends.
+pub fn diagnose(name: Option<&str>) -> Report {
+    let mut checks = vec![
+        check_tun_module(),
+        check_tun_device(),
+        check_net_admin(),
+        check_user_namespace(),
+    ];
+
+    if let Some(name) = name {
+        checks.push(check_interface_name(name));
+    }
+
+    Report { checks }
+}
+
+fn check(kind: CheckKind, severity: Severity, message: impl Into<String>) -> Check {
+    Check {
+        kind,
+        severity,
+        message: message.into(),
+    }
+}
+
+fn check_tun_device() -> Check {
+    let kind = CheckKind::TunDevice;
+
+    // Opening the device could load the driver, so only its permissions are checked.
+    match access("/dev/net/tun", AccessFlags::R_OK | AccessFlags::W_OK) {
+        Ok(()) => check(kind, Severity::Ok, "/dev/net/tun is accessible"),
+        Err(Errno::ENOENT) => check(
+            kind,
+            Severity::Error,
+            "/dev/net/tun doesn't exist; create it using `mkdir -p /dev/net && \
+             mknod /dev/net/tun c 10 200`, or pass `--device /dev/net/tun` to the container",
+        ),
+        Err(Errno::EACCES | Errno::EPERM) => check(
+            kind,
+            Severity::Error,
+            "/dev/net/tun is not accessible due to its permissions; run as root, or make it \
+             accessible using `chmod 0666 /dev/net/tun`",
+        ),
+        Err(errno) => check(
+            kind,
+            Severity::Error,
+            format!("/dev/net/tun is not accessible: {}", errno),
+        ),
+    }
+}
+
+fn check_tun_module() -> Check {
+    let kind = CheckKind::TunModule;
+
+    // The driver registers the misc device with the minor number 200 once it's loaded.
+    match fs::read_to_string("/proc/misc") {
+        Ok(misc)
+            if misc
+                .lines()
+                .any(|line| line.split_whitespace().eq(["200", "tun"])) =>
+        {
+            check(kind, Severity::Ok, "The TUN/TAP driver is loaded")
+        }
+        Ok(_) => check(
+            kind,
+            Severity::Warning,
+            "The TUN/TAP driver is not loaded yet; it's usually loaded on the first open of \
+             /dev/net/tun, otherwise load it using `modprobe tun`",
+        ),
+        Err(err) => check(
+            kind,
+            Severity::Warning,
+            format!("Couldn't read /proc/misc: {}", err),
+        ),
+    }
+}
+
+fn check_net_admin() -> Check {
+    let kind = CheckKind::NetAdminCapability;
+
+    let cap_eff = fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|status| {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix("CapEff:"))
+                .and_then(|value| u64::from_str_radix(value.trim(), 16).ok())
+        });
+
+    match cap_eff {
+        Some(caps) if caps & (1 << CAP_NET_ADMIN) != 0 => {
+            check(kind, Severity::Ok, "The process has CAP_NET_ADMIN")
+        }
+        Some(_) => check(
+            kind,
+            Severity::Error,
+            "The process doesn't have CAP_NET_ADMIN, which is required for creating and \
+             configuring devices; run as root, or grant it using `setcap cap_net_admin+ep <binary>`",
+        ),
+        None => check(
+            kind,
+            Severity::Warning,
+            "Couldn't read the capabilities of the process from /proc/self/status",
+        ),
+    }
+}
+
+fn check_user_namespace() -> Check {
+    let kind = CheckKind::UserNamespace;
+
+    // The initial user namespace maps the whole range of ids to itself.
+    let uid_map = match fs::read_to_string("/proc/self/uid_map") {
+        Ok(uid_map) => uid_map,
+        Err(err) => {
+            return check(
+                kind,
+                Severity::Warning,
+                format!("Couldn't read /proc/self/uid_map: {}", err),
+            )
+        }
+    };
+
+    let is_initial = uid_map
+        .lines()
+        .any(|line| line.split_whitespace().eq(["0", "0", "4294967295"]));
+
+    if is_initial {
+        return check(
+            kind,
+            Severity::Ok,
+            "The process runs in the initial user namespace",
+        );
+    }
+
+    let max_user_namespaces = fs::read_to_string("/proc/sys/user/max_user_namespaces")
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok());
+
+    let mut message = String::from(
+        "The process runs inside a user namespace, so its capabilities only apply to the network \
+         namespaces owned by that user namespace; create a network namespace along with it, \
+         e.g. using `unshare --user --map-root-user --net`",
+    );
+
+    if max_user_namespaces == Some(0) {
+        message.push_str("; creating user namespaces is disabled by user.max_user_namespaces");
+    }
+
+    check(kind, Severity::Warning, message)
+}
+
+fn check_interface_name(name: &str) -> Check {
+    let kind = CheckKind::InterfaceName;
+
+    let name = match InterfaceName::new(name) {
+        Ok(name) => name,
+        Err(err) => return check(kind, Severity::Error, err.to_string()),
+    };
+
+    if name.is_template() {
+        return check(
+            kind,
+            Severity::Ok,
+            format!("{} is a template which is completed by the kernel", name),
+        );
+    }
+
+    match find_interface(&name) {
+        Ok(interface) => check(
+            kind,
+            Severity::Warning,
+            format!(
+                "{} is in use by an existing {:?} interface; creating a device attaches to it \
+                 if the mode and flags are the same, otherwise choose another name",
+                name, interface.mode
+            ),
+        ),
+        Err(Error::DeviceNotFound(_)) => {
+            check(kind, Severity::Ok, format!("{} is not taken", name))
+        }
+        Err(Error::NotTunTap(_)) => check(
+            kind,
+            Severity::Error,
+            format!(
+                "{} is already used by an interface which is not a TUN/TAP interface; \
+                 choose another name",
+                name
+            ),
+        ),
+        Err(err) => check(
+            kind,
+            Severity::Warning,
+            format!("Couldn't look up {}: {}", name, err),
+        ),
+    }
+}
@@ -1,5 +1,7 @@
 use std::io;
 
+use nix::errno::Errno;
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug, thiserror::Error)]
@@ -13,15 +15,247 @@ pub enum Error {
     #[error("Nix error: {0}")]
     NixError(#[from] nix::Error),
 
+    /// The system call, ioctl or netlink request `op` (e.g. `open`, `TUNSETIFF` or
+    /// `RTM_NEWROUTE`) failed with `errno`.
+    #[error("{op} failed{}: {errno}", on_interface(.name))]
+    OsError {
+        op: &'static str,
+        name: Option<String>,
+        errno: Errno,
+    },
+
     #[error("Failed to create Flags from the data returned by the kernel: {0:b}")]
     ConversionError(i32),
+
+    #[error("Received a malformed netlink message from the kernel")]
+    MalformedNetlinkMessage,
+
+    #[error("Received a netlink message of {0} bytes which didn't fit into the receive buffer")]
+    NetlinkMessageTruncated(usize),
+
+    #[error("Netlink dump was interrupted by concurrent changes too many times")]
+    NetlinkDumpInterrupted,
+
+    #[error("Prefix length {0} is too long for the address family")]
+    InvalidPrefixLength(u8),
+
+    #[error("Addresses of different families can't be mixed together")]
+    AddressFamilyMismatch,
+
+    /// Reading or writing the file at `path`, e.g. a sysctl, failed with `errno`.
+    #[error("{op} of {path} failed: {errno}")]
+    FileError {
+        op: &'static str,
+        path: String,
+        errno: Errno,
+    },
+
+    #[error("Sysctl {0} contains an unexpected value: {1:?}")]
+    UnexpectedSysctlValue(String, String),
+
+    #[error("Sysfs file {0} contains an unexpected value: {1:?}")]
+    UnexpectedSysfsValue(String, String),
+
+    #[error("Invalid interface name {0:?}: {1}")]
+    InvalidInterfaceName(String, &'static str),
+
+    #[error("Interface {0} doesn't exist")]
+    DeviceNotFound(String),
+
+    #[error("Interface {0} is not a TUN/TAP interface")]
+    NotTunTap(String),
+
+    #[error("Interface {0} is in use by another process")]
+    DeviceBusy(String),
+
+    #[error("Packet didn't fit into the buffer of {0} bytes and was truncated")]
+    PacketTruncated(usize),
+
+    #[error("Operation timed out")]
+    TimedOut,
+
+    #[error("Queue of interface {0} is detached")]
+    QueueDetached(String),
+
+    /// The queue was attached or detached, but the steering settings of the queues reindexed
+    /// by the kernel couldn't be written to their new indices. They're written again on the
+    /// next attach or detach of a queue of the interface.
+    #[error("Failed to move the steering settings of the reindexed queues of interface {0}: {1}")]
+    SteeringNotRestored(String, #[source] Box<Error>),
+
+    #[error("Worker of queue {0} panicked")]
+    WorkerPanicked(usize),
+
+    #[error("CPU {0} is not in the set of CPUs the process is allowed to run on")]
+    CpuNotAllowed(usize),
+}
+
+impl Error {
+    /// Returns the errno of the failed system call which caused the error, if any.
+    pub fn errno(&self) -> Option<Errno> {
+        match self {
+            Error::IOError(io_err) => io_err.raw_os_error().map(Errno::from_i32),
+            Error::NixError(errno)
+            | Error::OsError { errno, .. }
+            | Error::FileError { errno, .. } => Some(*errno),
+            _ => None,
+        }
+    }
+
+    /// Returns the operation which failed, if known.
+    pub fn op(&self) -> Option<&'static str> {
+        match self {
+            Error::OsError { op, .. } | Error::FileError { op, .. } => Some(op),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the interface the failed operation was performed on, if known.
+    pub fn interface_name(&self) -> Option<&str> {
+        match self {
+            Error::OsError { name, .. } => name.as_deref(),
+            Error::InvalidInterfaceName(name, _)
+            | Error::DeviceNotFound(name)
+            | Error::NotTunTap(name)
+            | Error::DeviceBusy(name)
+            | Error::QueueDetached(name)
+            | Error::SteeringNotRestored(name, _) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the process lacks the privileges(usually `CAP_NET_ADMIN`) for the operation.
+    pub fn is_permission_denied(&self) -> bool {
+        matches!(self.errno(), Some(Errno::EPERM | Errno::EACCES))
+    }
+
+    /// Returns whether the requested interface name is already taken, either by an
+    /// interface of another kind or by a TUN/TAP interface which is in use.
+    pub fn is_name_in_use(&self) -> bool {
+        match self.errno() {
+            // The kernel doesn't accept the name if it belongs to a non-TUN/TAP interface,
+            // or to a TUN/TAP interface created with different flags.
+            Some(Errno::EINVAL) => self.op() == Some("TUNSETIFF"),
+            Some(errno) => matches!(errno, Errno::EEXIST | Errno::EBUSY),
+            None => matches!(self, Error::DeviceBusy(_)),
+        }
+    }
+
+    /// Returns whether the operation is not supported by the running kernel, e.g. the
+    /// TUN/TAP driver is missing or IPv6 is disabled.
+    pub fn is_unsupported(&self) -> bool {
+        match self.errno() {
+            // The IPv6 sysctls are missing if IPv6 is disabled.
+            Some(Errno::ENOENT) => match self {
+                Error::FileError { path, .. } => path.starts_with("/proc/sys/net/ipv6/"),
+                _ => self.op() == Some(OPEN_TUN),
+            },
+            Some(errno) => matches!(
+                errno,
+                Errno::EOPNOTSUPP
+                    | Errno::EAFNOSUPPORT
+                    | Errno::EPROTONOSUPPORT
+                    | Errno::ENOSYS
+                    | Errno::ENOTTY
+            ),
+            None => false,
+        }
+    }
+
+    /// Returns whether the interface doesn't exist anymore, e.g. it was deleted by another process.
+    pub fn is_device_gone(&self) -> bool {
+        matches!(self, Error::DeviceNotFound(_))
+            || matches!(
+                self.errno(),
+                Some(Errno::ENODEV | Errno::ENXIO | Errno::EBADFD)
+            )
+    }
+
+    // Recovers the error wrapped into an `io::Error` by `From<Error> for io::Error`, e.g. by
+    // the closures passed to the I/O methods of the async runtimes.
+    #[cfg(any(feature = "tokio", feature = "async-io"))]
+    pub(crate) fn from_io(err: io::Error) -> Self {
+        if err.get_ref().is_some_and(|inner| inner.is::<Error>()) {
+            return *err.into_inner().unwrap().downcast::<Error>().unwrap();
+        }
+
+        err.into()
+    }
+
+    // Attaches the operation and the interface name to an error caused by a failed system call.
+    fn with_context(self, op: &'static str, name: Option<&str>) -> Self {
+        match self.errno() {
+            // Would-block is part of the normal operation of non-blocking devices,
+            // so we don't pay for the allocation of the name.
+            Some(Errno::EAGAIN) => self,
+            Some(errno) if !matches!(self, Error::OsError { .. } | Error::FileError { .. }) => {
+                Error::OsError {
+                    op,
+                    name: name.map(String::from),
+                    errno,
+                }
+            }
+            _ => self,
+        }
+    }
+}
+
+// Operation of opening the TUN/TAP clone device, `/dev/net/tun`.
+pub(crate) const OPEN_TUN: &str = "open";
+
+fn on_interface(name: &Option<String>) -> String {
+    name.as_ref()
+        .map(|name| format!(" on interface {}", name))
+        .unwrap_or_default()
+}
+
+// Attaches the context of the operation to the errors of the system calls.
+pub(crate) trait Context<T> {
+    fn context(self, op: &'static str, name: &str) -> Result<T>;
+
+    fn op_context(self, op: &'static str) -> Result<T>;
+
+    // Attaches the path of the file which failed to be read or written.
+    fn path_context(self, op: &'static str, path: &str) -> Result<T>;
+}
+
+impl<T, E: Into<Error>> Context<T> for std::result::Result<T, E> {
+    fn context(self, op: &'static str, name: &str) -> Result<T> {
+        self.map_err(|err| err.into().with_context(op, Some(name)))
+    }
+
+    fn op_context(self, op: &'static str) -> Result<T> {
+        self.map_err(|err| err.into().with_context(op, None))
+    }
+
+    fn path_context(self, op: &'static str, path: &str) -> Result<T> {
+        self.map_err(|err| {
+            let err = err.into();
+
+            match err.errno() {
+                Some(errno) => Error::FileError {
+                    op,
+                    path: path.to_string(),
+                    errno,
+                },
+                None => err,
+            }
+        })
+    }
 }
 
 impl From<Error> for io::Error {
     fn from(err: Error) -> Self {
         match err {
             Error::IOError(io_err) => io_err,
-            _ => io::Error::new(io::ErrorKind::Other, err),
+            // Keep the errno so the kind of the error(e.g. `WouldBlock`) is preserved.
+            Error::NixError(errno) => errno.into(),
+            Error::OsError { errno, .. } | Error::FileError { errno, .. } => {
+                io::Error::new(io::Error::from(errno).kind(), err)
+            }
+            Error::PacketTruncated(..) => io::Error::new(io::ErrorKind::InvalidData, err),
+            Error::TimedOut => io::Error::new(io::ErrorKind::TimedOut, err),
+            _ => io::Error::other(err),
         }
     }
 }
@@ -0,0 +1,43 @@
+use std::io;
+use std::os::unix::prelude::AsRawFd;
+
+use mio::event::Source;
+use mio::unix::SourceFd;
+use mio::{Interest, Registry, Token};
+
+use crate::{Device, MQDevice, MQTap, MQTun, Tap, Tun};
+
+// Registers the file descriptor of the device, so the device should be non-blocking,
+// e.g. created using `Tun::new_nonblocking`.
+macro_rules! impl_source {
+    ($($ty:ty),*) => {
+        $(
+            #[cfg_attr(docsrs, doc(cfg(feature = "mio")))]
+            impl Source for $ty {
+                fn register(
+                    &mut self,
+                    registry: &Registry,
+                    token: Token,
+                    interests: Interest,
+                ) -> io::Result<()> {
+                    SourceFd(&self.as_raw_fd()).register(registry, token, interests)
+                }
+
+                fn reregister(
+                    &mut self,
+                    registry: &Registry,
+                    token: Token,
+                    interests: Interest,
+                ) -> io::Result<()> {
+                    SourceFd(&self.as_raw_fd()).reregister(registry, token, interests)
+                }
+
+                fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+                    SourceFd(&self.as_raw_fd()).deregister(registry)
+                }
+            }
+        )*
+    };
+}
+
+impl_source!(Device, Tun, Tap, MQDevice, MQTun, MQTap);
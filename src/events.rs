@@ -0,0 +1,326 @@
+//! Notifications about the changes made to a TUN/TAP device.
+
+use std::collections::{HashSet, VecDeque};
+use std::net;
+use std::os::unix::prelude::*;
+
+use nix::sys::socket::SockFlag;
+
+use crate::bindings;
+use crate::error::{Context, Result};
+use crate::handle::InterfaceHandle;
+use crate::netlink::{self, Message, Netlink};
+
+/// A change made to the device, possibly by another process.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Event {
+    /// The device was brought up.
+    Up,
+
+    /// The device was brought down.
+    Down,
+
+    /// The carrier of the device was gained (`true`) or lost (`false`).
+    CarrierChanged(bool),
+
+    /// The MTU of the device was changed to the contained value.
+    MtuChanged(u32),
+
+    /// The device was renamed to the contained name.
+    Renamed(String),
+
+    /// An address was added to the device.
+    AddressAdded { addr: net::IpAddr, prefix_len: u8 },
+
+    /// An address was removed from the device.
+    AddressRemoved { addr: net::IpAddr, prefix_len: u8 },
+
+    /// The device was moved to another network namespace.
+    NamespaceMoved,
+
+    /// The device was removed.
+    ///
+    /// This is also reported when the device was moved to a network namespace which
+    /// doesn't have an id in the current namespace.
+    Removed,
+}
+
+// The last known state of the link, used for finding out what changed in a notification.
+#[derive(Debug, Default)]
+struct Link {
+    flags: u32,
+    mtu: Option<u32>,
+    name: Option<String>,
+}
+
+impl Link {
+    // Parses a `RTM_NEWLINK`/`RTM_DELLINK` message and returns the index of the link with its state.
+    fn from_response(response: &netlink::Response) -> Option<(i32, Self, bool)> {
+        let ifinfomsg: bindings::ifinfomsg = response.header()?;
+
+        let mut link = Link {
+            flags: ifinfomsg.ifi_flags,
+            ..Default::default()
+        };
+        let mut has_new_netnsid = false;
+
+        for (ty, payload) in response.attrs::<bindings::ifinfomsg>() {
+            match ty {
+                nix::libc::IFLA_MTU => link.mtu = netlink::to_u32(payload),
+                nix::libc::IFLA_IFNAME => link.name = Some(netlink::to_string(payload)),
+                nix::libc::IFLA_NEW_NETNSID => has_new_netnsid = true,
+                _ => {}
+            }
+        }
+
+        Some((ifinfomsg.ifi_index, link, has_new_netnsid))
+    }
+}
+
+/// A blocking subscription to the [`Event`]s of a device.
+///
+/// Can be created using [`InterfaceHandle::events`]. Iterating over it blocks until the next event.
+#[derive(Debug)]
+pub struct Events {
+    socket: Netlink,
+    ifindex: i32,
+    link: Link,
+    // Addresses of the device, since an address is notified again when its flags change,
+    // e.g. when IPv6 duplicate address detection completes.
+    addrs: HashSet<(net::IpAddr, u8)>,
+    pending: VecDeque<Event>,
+}
+
+impl Events {
+    fn new(handle: &InterfaceHandle, flags: SockFlag) -> Result<Self> {
+        let ifindex = handle.get_index()?;
+
+        // Subscribe before reading the current state so no change is missed in between.
+        let groups =
+            nix::libc::RTMGRP_LINK | nix::libc::RTMGRP_IPV4_IFADDR | nix::libc::RTMGRP_IPV6_IFADDR;
+        let socket = Netlink::bind(groups as u32, flags)?;
+
+        let mut ifinfomsg: bindings::ifinfomsg = unsafe { std::mem::zeroed() };
+        ifinfomsg.ifi_index = ifindex;
+
+        let msg = Message::new(nix::libc::RTM_GETLINK, 0, &ifinfomsg);
+        let response = Netlink::new()?
+            .get(msg)
+            .context("RTM_GETLINK", &handle.name)?;
+        let (_, link, _) = Link::from_response(&response).unwrap_or_default();
+
+        let ifaddrmsg: bindings::ifaddrmsg = unsafe { std::mem::zeroed() };
+        let msg = Message::new(nix::libc::RTM_GETADDR, 0, &ifaddrmsg);
+        let addrs = Netlink::new()?
+            .dump(msg)
+            .context("RTM_GETADDR", &handle.name)?
+            .iter()
+            .filter_map(parse_addr)
+            .filter(|(index, _, _)| *index == ifindex)
+            .map(|(_, addr, prefix_len)| (addr, prefix_len))
+            .collect();
+
+        Ok(Events {
+            socket,
+            ifindex,
+            link,
+            addrs,
+            pending: VecDeque::new(),
+        })
+    }
+
+    /// Blocks until the next event of the device and returns it.
+    pub fn next_event(&mut self) -> Result<Event> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(event);
+            }
+
+            self.read_events()?;
+        }
+    }
+
+    // Receives a batch of notifications and queues the events related to our device.
+    fn read_events(&mut self) -> Result<()> {
+        for response in self.socket.recv().op_context("recv")? {
+            match response.ty {
+                nix::libc::RTM_NEWLINK => self.on_new_link(&response),
+                nix::libc::RTM_DELLINK => self.on_del_link(&response),
+                nix::libc::RTM_NEWADDR | nix::libc::RTM_DELADDR => self.on_addr(&response),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn on_new_link(&mut self, response: &netlink::Response) {
+        let link = match Link::from_response(response) {
+            Some((ifindex, link, _)) if ifindex == self.ifindex => link,
+            _ => return,
+        };
+
+        let changed = |flag: i32| (self.link.flags ^ link.flags) & flag as u32 != 0;
+        let is_set = |flag: i32| link.flags & flag as u32 != 0;
+
+        if changed(nix::libc::IFF_UP) {
+            self.pending.push_back(if is_set(nix::libc::IFF_UP) {
+                Event::Up
+            } else {
+                Event::Down
+            });
+        }
+
+        if changed(nix::libc::IFF_LOWER_UP) {
+            let carrier = is_set(nix::libc::IFF_LOWER_UP);
+            self.pending.push_back(Event::CarrierChanged(carrier));
+        }
+
+        if let Some(mtu) = link.mtu.filter(|mtu| self.link.mtu != Some(*mtu)) {
+            self.pending.push_back(Event::MtuChanged(mtu));
+        }
+
+        if let Some(name) = link
+            .name
+            .as_ref()
+            .filter(|name| self.link.name.as_ref() != Some(name))
+        {
+            self.pending.push_back(Event::Renamed(name.clone()));
+        }
+
+        self.link = link;
+    }
+
+    fn on_del_link(&mut self, response: &netlink::Response) {
+        if let Some((ifindex, _, has_new_netnsid)) = Link::from_response(response) {
+            if ifindex == self.ifindex {
+                self.pending.push_back(if has_new_netnsid {
+                    Event::NamespaceMoved
+                } else {
+                    Event::Removed
+                });
+            }
+        }
+    }
+
+    fn on_addr(&mut self, response: &netlink::Response) {
+        let (addr, prefix_len) = match parse_addr(response) {
+            Some((index, addr, prefix_len)) if index == self.ifindex => (addr, prefix_len),
+            _ => return,
+        };
+
+        if response.ty == nix::libc::RTM_NEWADDR {
+            if self.addrs.insert((addr, prefix_len)) {
+                self.pending
+                    .push_back(Event::AddressAdded { addr, prefix_len });
+            }
+        } else {
+            self.addrs.remove(&(addr, prefix_len));
+            self.pending
+                .push_back(Event::AddressRemoved { addr, prefix_len });
+        }
+    }
+}
+
+// Parses a `RTM_NEWADDR`/`RTM_DELADDR` message and returns the index of the link with the
+// address and its prefix length.
+fn parse_addr(response: &netlink::Response) -> Option<(i32, net::IpAddr, u8)> {
+    let ifaddrmsg: bindings::ifaddrmsg = response.header()?;
+
+    // For point-to-point links, `IFA_ADDRESS` is the address of the other end
+    // and `IFA_LOCAL` is our address.
+    let mut addr = None;
+    for (ty, payload) in response.attrs::<bindings::ifaddrmsg>() {
+        match ty {
+            nix::libc::IFA_LOCAL => addr = netlink::to_ip(payload),
+            nix::libc::IFA_ADDRESS if addr.is_none() => addr = netlink::to_ip(payload),
+            _ => {}
+        }
+    }
+
+    Some((ifaddrmsg.ifa_index as i32, addr?, ifaddrmsg.ifa_prefixlen))
+}
+
+impl Iterator for Events {
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_event())
+    }
+}
+
+impl AsRawFd for Events {
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+}
+
+impl InterfaceHandle {
+    /// Subscribes to the link and address changes of the device.
+    pub fn events(&self) -> Result<Events> {
+        Events::new(self, SockFlag::empty())
+    }
+}
+
+#[cfg(feature = "tokio")]
+pub use self::tokio_events::AsyncEvents;
+
+#[cfg(feature = "tokio")]
+mod tokio_events {
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use nix::sys::socket::SockFlag;
+    use tokio::io::unix::AsyncFd;
+
+    use super::{Event, Events};
+    use crate::asyncd::AsyncDevice;
+    use crate::error::{Error, Result};
+
+    /// A non-blocking subscription to the [`Event`]s of a device.
+    ///
+    /// Can be created using [`AsyncDevice::events`]. It's also a `Stream` of the events.
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+    #[derive(Debug)]
+    pub struct AsyncEvents(AsyncFd<Events>);
+
+    impl AsyncEvents {
+        /// Asynchronously waits for the next event of the device and returns it.
+        pub async fn next_event(&mut self) -> Result<Event> {
+            futures::future::poll_fn(|cx| self.poll_next_event(cx)).await
+        }
+
+        fn poll_next_event(&mut self, cx: &mut Context<'_>) -> Poll<Result<Event>> {
+            loop {
+                if let Some(event) = self.0.get_mut().pending.pop_front() {
+                    return Poll::Ready(Ok(event));
+                }
+
+                let mut guard = futures::ready!(self.0.poll_read_ready_mut(cx))?;
+
+                match guard.try_io(|inner| Ok(inner.get_mut().read_events()?)) {
+                    Ok(result) => result.map_err(Error::from_io)?,
+                    Err(_would_block) => continue,
+                }
+            }
+        }
+    }
+
+    impl futures::Stream for AsyncEvents {
+        type Item = Result<Event>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            self.get_mut().poll_next_event(cx).map(Some)
+        }
+    }
+
+    impl AsyncDevice {
+        /// Subscribes to the link and address changes of the device.
+        #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+        pub fn events(&self) -> Result<AsyncEvents> {
+            let events = Events::new(self, SockFlag::SOCK_NONBLOCK)?;
+
+            Ok(AsyncEvents(AsyncFd::new(events)?))
+        }
+    }
+}
@@ -73,3 +73,40 @@ impl TryFrom<i32> for Flags {
         Flags::from_bits(value).ok_or(Error::ConversionError(value))
     }
 }
+
+bitflags::bitflags! {
+    /// Bitflags of a TUN/TAP device, set by the program which created it.
+    ///
+    /// For more info: `Documentation/networking/tuntap.rst` in the kernel source tree.
+    pub struct TunFlags: i32 {
+        /// Device is a TUN device.
+        const IFF_TUN = nix::libc::IFF_TUN;
+
+        /// Device is a TAP device.
+        const IFF_TAP = nix::libc::IFF_TAP;
+
+        /// Device uses NAPI for the packets written to it.
+        const IFF_NAPI = nix::libc::IFF_NAPI;
+
+        /// Device uses NAPI and accepts fragmented frames.
+        const IFF_NAPI_FRAGS = nix::libc::IFF_NAPI_FRAGS;
+
+        /// Device has multiple queues.
+        const IFF_MULTI_QUEUE = nix::libc::IFF_MULTI_QUEUE;
+
+        /// Device is not destroyed when its last file descriptor is closed.
+        const IFF_PERSIST = nix::libc::IFF_PERSIST;
+
+        /// Packets are not prefixed with the packet information header.
+        const IFF_NO_PI = nix::libc::IFF_NO_PI;
+
+        /// Obsolete flag, ignored by the kernel.
+        const IFF_ONE_QUEUE = nix::libc::IFF_ONE_QUEUE;
+
+        /// Packets are prefixed with a virtio-net header.
+        const IFF_VNET_HDR = nix::libc::IFF_VNET_HDR;
+
+        /// Device can't be attached to, if it already exists.
+        const IFF_TUN_EXCL = nix::libc::IFF_TUN_EXCL;
+    }
+}
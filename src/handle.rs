@@ -0,0 +1,408 @@
+use std::net;
+use std::os::unix::prelude::*;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+
+use nix::errno::Errno;
+use nix::sys::socket;
+
+use crate::error::{Context, Error, Result};
+use crate::flags::Flags;
+use crate::{bindings, ioctl, sockaddr, InterfaceName};
+
+/// A handle for configuring a network interface, which doesn't own any of its queues.
+///
+/// It can be obtained from a device using [`Device::handle`](crate::Device::handle), or looked
+/// up using [`InterfaceHandle::from_name`] and [`InterfaceHandle::from_index`]. It's cheap to
+/// clone and can be sent to other threads, so a thread can configure the interface while the
+/// other threads do the I/O.
+///
+/// The handle refers to the interface by its index, so it keeps following the interface when
+/// it's renamed, and fails with [`Error::DeviceNotFound`] once the interface is deleted, even if
+/// another interface is created with the same name.
+///
+/// All handles of the process share the same control sockets.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InterfaceHandle {
+    // Name of the interface when the handle was created, used for reporting errors.
+    pub(crate) name: Arc<InterfaceName>,
+    index: u32,
+}
+
+impl InterfaceHandle {
+    pub(crate) fn new(name: InterfaceName, index: u32) -> Self {
+        InterfaceHandle {
+            name: Arc::new(name),
+            index,
+        }
+    }
+
+    /// Returns a handle to the existing interface called `name`.
+    ///
+    /// # Returns
+    /// * `Ok`: If the interface exists.
+    /// * `Err(Error::DeviceNotFound)`: If there is no interface called `name`.
+    /// * `Err`: If `name` is not a valid interface name.
+    pub fn from_name(name: impl AsRef<str>) -> Result<Self> {
+        let name = InterfaceName::new(name.as_ref())?;
+
+        match nix::net::if_::if_nametoindex(name.as_str()) {
+            Ok(index) => Ok(InterfaceHandle::new(name, index)),
+            Err(Errno::ENODEV) => Err(Error::DeviceNotFound(name.to_string())),
+            Err(errno) => Err(errno).context("if_nametoindex", &name),
+        }
+    }
+
+    /// Returns a handle to the existing interface with the index of `index`.
+    pub fn from_index(index: u32) -> Result<Self> {
+        let name = index_to_name(index).map_err(|err| match err {
+            Error::DeviceNotFound(_) => Error::DeviceNotFound(index.to_string()),
+            err => err,
+        })?;
+
+        Ok(InterfaceHandle::new(name, index))
+    }
+
+    /// Returns the name the interface had when the handle was created.
+    ///
+    /// See [`current_name`](Self::current_name) for the name of a renamed interface.
+    pub fn name(&self) -> &InterfaceName {
+        &self.name
+    }
+
+    /// Returns the current name of the interface.
+    ///
+    /// # Returns
+    /// * `Ok`: If the interface still exists.
+    /// * `Err(Error::DeviceNotFound)`: If the interface was deleted.
+    pub fn current_name(&self) -> Result<InterfaceName> {
+        index_to_name(self.index).map_err(|err| match err {
+            Error::DeviceNotFound(_) => Error::DeviceNotFound(self.name.to_string()),
+            err => err,
+        })
+    }
+
+    /// Returns the active flags of the interface.
+    pub fn flags(&self) -> Result<Flags> {
+        self.read_flags()?.try_into()
+    }
+
+    /// Brings the device up which makes it ready to send and receive packets.
+    pub fn bring_up(&self) -> Result<()> {
+        self.add_flags(nix::libc::IFF_UP | nix::libc::IFF_RUNNING)
+    }
+
+    /// Brings the device down which makes it unable to send and receive packets.
+    pub fn bring_down(&self) -> Result<()> {
+        self.del_flags(nix::libc::IFF_UP | nix::libc::IFF_RUNNING)
+    }
+
+    /// Sets the MTU of the device.
+    pub fn set_mtu(&self, mtu: i32) -> Result<()> {
+        let mut ifreq = self.new_ifreq()?;
+
+        ifreq.ifr_ifru.ifru_mtu = mtu;
+
+        unsafe { ioctl::siocsifmtu(inet4_socket()?, &ifreq as *const bindings::ifreq) }
+            .context("SIOCSIFMTU", &self.name)?;
+
+        Ok(())
+    }
+
+    /// Returns the MTU of the device.
+    pub fn get_mtu(&self) -> Result<i32> {
+        let mut ifreq = self.new_ifreq()?;
+
+        unsafe { ioctl::siocgifmtu(inet4_socket()?, &mut ifreq as *mut bindings::ifreq) }
+            .context("SIOCGIFMTU", &self.name)?;
+
+        // Safety:
+        //
+        // Since we issued an ioctl for getting the MTU, it's safe to assume
+        // that if the ioctl was successfull, kernel had set the `ifru_mtu` variant.
+        Ok(unsafe { ifreq.ifr_ifru.ifru_mtu })
+    }
+
+    /// Sets the netmask of the device.
+    pub fn set_netmask(&self, netmask: net::Ipv4Addr) -> Result<()> {
+        let mut ifreq = self.new_ifreq()?;
+
+        ifreq.ifr_ifru.ifru_netmask = sockaddr::to_sockaddr(netmask);
+
+        unsafe { ioctl::siocsifnetmask(inet4_socket()?, &ifreq as *const bindings::ifreq) }
+            .context("SIOCSIFNETMASK", &self.name)?;
+
+        Ok(())
+    }
+
+    /// Sets the netmask of the device.
+    pub fn get_netmask(&self) -> Result<net::Ipv4Addr> {
+        let mut ifreq = self.new_ifreq()?;
+
+        unsafe { ioctl::siocgifnetmask(inet4_socket()?, &mut ifreq as *mut bindings::ifreq) }
+            .context("SIOCGIFNETMASK", &self.name)?;
+
+        // Safety:
+        //
+        // Since we issued an ioctl for getting the netmask, it's safe to assume
+        // that if the ioctl was successfull, kernel had set the `ifru_netmask` variant.
+        Ok(sockaddr::to_ipv4(unsafe { ifreq.ifr_ifru.ifru_netmask }))
+    }
+
+    /// Returns the index of the interface.
+    pub fn get_index(&self) -> Result<i32> {
+        Ok(self.index as i32)
+    }
+
+    /// Adds the specified `addr` to the list of IPv6 addresses of the interface.
+    pub fn set_ipv6_addr(&self, addr: net::Ipv6Addr) -> Result<()> {
+        let ifindex = self.get_index()?;
+
+        #[rustfmt::skip]
+        let in6_ifreq = bindings::in6_ifreq {
+            ifr6_addr: nix::libc::in6_addr { s6_addr: addr.octets() },
+            ifr6_prefixlen: 64,
+            ifr6_ifindex: ifindex,
+        };
+
+        unsafe { ioctl::siocsifaddr6(inet6_socket()?, &in6_ifreq as *const bindings::in6_ifreq) }
+            .context("SIOCSIFADDR", &self.name)?;
+
+        Ok(())
+    }
+
+    /// Returns the list of IPv6 addresses of the interface.
+    pub fn get_ipv6_addrs(&self) -> Result<Vec<net::Ipv6Addr>> {
+        let name = self.current_name()?;
+
+        // `getifaddrs` returns all addresses of all interfaces in the system.
+        Ok(nix::ifaddrs::getifaddrs()
+            .op_context("getifaddrs")?
+            // We are only interested in the addresses related to our interface.
+            .filter(|iface| name == iface.interface_name)
+            // Among the addresses related to our interface, we want the IPv6 ones.
+            .filter_map(|iface| {
+                iface
+                    .address
+                    .and_then(|addr| addr.as_sockaddr_in6().map(|in6_addr| in6_addr.ip()))
+                //                   ----------------------                -------------
+                //                     try to convert the                   extract the
+                //                     address to IPv6                      ip from IPv6
+            })
+            .collect())
+    }
+
+    /// Deletes the specified IPv6 address from the interface.
+    pub fn del_ipv6_addr(&self, addr: net::Ipv6Addr) -> Result<()> {
+        let ifindex = self.get_index()?;
+
+        #[rustfmt::skip]
+        let in6_ifreq = bindings::in6_ifreq {
+            ifr6_addr: nix::libc::in6_addr { s6_addr: addr.octets() },
+            ifr6_prefixlen: 64,
+            ifr6_ifindex: ifindex,
+        };
+
+        unsafe { ioctl::siocdifaddr6(inet6_socket()?, &in6_ifreq as *const bindings::in6_ifreq) }
+            .context("SIOCDIFADDR", &self.name)?;
+
+        Ok(())
+    }
+
+    /// Sets the IPv4 address of the device.
+    pub fn set_addr(&self, addr: net::Ipv4Addr) -> Result<()> {
+        let mut ifreq = self.new_ifreq()?;
+
+        ifreq.ifr_ifru.ifru_addr = sockaddr::to_sockaddr(addr);
+
+        unsafe { ioctl::siocsifaddr(inet4_socket()?, &ifreq as *const bindings::ifreq) }
+            .context("SIOCSIFADDR", &self.name)?;
+
+        Ok(())
+    }
+
+    /// Returns the IPv4 address of the device.
+    pub fn get_addr(&self) -> Result<net::Ipv4Addr> {
+        let mut ifreq = self.new_ifreq()?;
+
+        unsafe { ioctl::siocgifaddr(inet4_socket()?, &mut ifreq as *mut bindings::ifreq) }
+            .context("SIOCGIFADDR", &self.name)?;
+
+        // Safety:
+        //
+        // Since we issued a ioctl for getting the netmask, it's safe to assume
+        // that if the ioctl was successfull, kernel had set the `ifru_netmask` variant.
+        Ok(sockaddr::to_ipv4(unsafe { ifreq.ifr_ifru.ifru_addr }))
+    }
+
+    /// Deletes the IPv4 address of the interface.
+    pub fn del_addr(&self) -> Result<()> {
+        let mut ifreq = self.new_ifreq()?;
+
+        ifreq.ifr_ifru.ifru_addr =
+            sockaddr::to_sockaddr(net::Ipv4Addr::from_str("0.0.0.0").unwrap());
+
+        unsafe { ioctl::siocsifaddr(inet4_socket()?, &ifreq as *const bindings::ifreq) }
+            .context("SIOCSIFADDR", &self.name)?;
+
+        Ok(())
+    }
+
+    /// Sets the broadcast IPv4 address of the device.
+    pub fn set_brd_addr(&self, addr: net::Ipv4Addr) -> Result<()> {
+        let mut ifreq = self.new_ifreq()?;
+
+        ifreq.ifr_ifru.ifru_broadaddr = sockaddr::to_sockaddr(addr);
+
+        unsafe { ioctl::siocsifbrdaddr(inet4_socket()?, &ifreq as *const bindings::ifreq) }
+            .context("SIOCSIFBRDADDR", &self.name)?;
+
+        Ok(())
+    }
+
+    /// Returns the broadcast IPv4 address of the device.
+    pub fn get_brd_addr(&self) -> Result<net::Ipv4Addr> {
+        let mut ifreq = self.new_ifreq()?;
+
+        unsafe { ioctl::siocgifbrdaddr(inet4_socket()?, &mut ifreq as *mut bindings::ifreq) }
+            .context("SIOCGIFBRDADDR", &self.name)?;
+
+        // Safety:
+        //
+        // Since we issued a ioctl for getting the broadcast address, it's safe to assume
+        // that if the ioctl was successfull, kernel had set the `ifru_broadaddr` variant.
+        Ok(sockaddr::to_ipv4(unsafe { ifreq.ifr_ifru.ifru_broadaddr }))
+    }
+
+    /// Sets the destination IPv4 address of the device.
+    pub fn set_dst_addr(&self, addr: net::Ipv4Addr) -> Result<()> {
+        let mut ifreq = self.new_ifreq()?;
+
+        ifreq.ifr_ifru.ifru_dstaddr = sockaddr::to_sockaddr(addr);
+
+        unsafe { ioctl::siocsifdstaddr(inet4_socket()?, &ifreq as *const bindings::ifreq) }
+            .context("SIOCSIFDSTADDR", &self.name)?;
+
+        Ok(())
+    }
+
+    /// Returns the destination IPv4 address of the device.
+    pub fn get_dst_addr(&self) -> Result<net::Ipv4Addr> {
+        let mut ifreq = self.new_ifreq()?;
+
+        unsafe { ioctl::siocgifdstaddr(inet4_socket()?, &mut ifreq as *mut bindings::ifreq) }
+            .context("SIOCGIFDSTADDR", &self.name)?;
+
+        // Safety:
+        //
+        // Since we issued a ioctl for getting the destination address, it's safe to assume
+        // that if the ioctl was successfull, kernel had set the `ifru_dstaddr` variant.
+        Ok(sockaddr::to_ipv4(unsafe { ifreq.ifr_ifru.ifru_dstaddr }))
+    }
+
+    // Returns an empty ifreq with the current name of this interface.
+    fn new_ifreq(&self) -> Result<bindings::ifreq> {
+        let mut ifreq: bindings::ifreq = unsafe { std::mem::zeroed() };
+
+        ifreq.ifr_ifrn.ifrn_name = self.current_name()?.to_ifr_name();
+
+        Ok(ifreq)
+    }
+
+    // Returns the active flags of the device.
+    fn read_flags(&self) -> Result<i32> {
+        let mut ifreq = self.new_ifreq()?;
+
+        unsafe { ioctl::siocgifflags(inet4_socket()?, &mut ifreq as *mut bindings::ifreq) }
+            .context("SIOCGIFFLAGS", &self.name)?;
+
+        Ok(unsafe { ifreq.ifr_ifru.ifru_flags.into() })
+    }
+
+    fn add_flags(&self, flags: i32) -> Result<()> {
+        let mut ifreq = self.new_ifreq()?;
+
+        ifreq.ifr_ifru.ifru_flags = self.read_flags()? as i16;
+
+        unsafe {
+            ifreq.ifr_ifru.ifru_flags |= flags as i16;
+
+            ioctl::siocsifflags(inet4_socket()?, &ifreq as *const bindings::ifreq)
+        }
+        .context("SIOCSIFFLAGS", &self.name)?;
+
+        Ok(())
+    }
+
+    fn del_flags(&self, flags: i32) -> Result<()> {
+        let mut ifreq = self.new_ifreq()?;
+
+        ifreq.ifr_ifru.ifru_flags = self.read_flags()? as i16;
+
+        unsafe {
+            ifreq.ifr_ifru.ifru_flags &= !(flags) as i16;
+
+            ioctl::siocsifflags(inet4_socket()?, &ifreq as *const bindings::ifreq)
+        }
+        .context("SIOCSIFFLAGS", &self.name)?;
+
+        Ok(())
+    }
+}
+
+// Returns the name of the interface with the index of `index`.
+//
+// Asked through the shared control socket, since libc's `if_indextoname` opens a new socket
+// on every call.
+fn index_to_name(index: u32) -> Result<InterfaceName> {
+    let mut ifreq: bindings::ifreq = unsafe { std::mem::zeroed() };
+    ifreq.ifr_ifru.ifru_ivalue = index as i32;
+
+    match unsafe { ioctl::siocgifname(inet4_socket()?, &mut ifreq as *mut bindings::ifreq) } {
+        Err(Errno::ENODEV) => return Err(Error::DeviceNotFound(index.to_string())),
+        result => result.op_context("SIOCGIFNAME")?,
+    };
+
+    // Safety:
+    //
+    // The kernel sets the name of the interface if the ioctl was successful.
+    Ok(InterfaceName::from_ifr_name(unsafe {
+        &ifreq.ifr_ifrn.ifrn_name
+    }))
+}
+
+// Returns the process-wide control socket of `family`, creating it on the first call.
+fn control_socket(
+    cell: &'static OnceLock<OwnedFd>,
+    family: socket::AddressFamily,
+) -> Result<RawFd> {
+    if let Some(fd) = cell.get() {
+        return Ok(fd.as_raw_fd());
+    }
+
+    // Configuration ioctls can be issued on any socket. We use a UDP socket since it's the
+    // cheapest one to create.
+    let fd = socket::socket(
+        family,
+        socket::SockType::Datagram,
+        socket::SockFlag::SOCK_CLOEXEC,
+        None,
+    )
+    .op_context("socket")?;
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    // If another thread won the race, our socket is closed here.
+    Ok(cell.get_or_init(|| fd).as_raw_fd())
+}
+
+fn inet4_socket() -> Result<RawFd> {
+    static SOCKET: OnceLock<OwnedFd> = OnceLock::new();
+
+    control_socket(&SOCKET, socket::AddressFamily::Inet)
+}
+
+fn inet6_socket() -> Result<RawFd> {
+    static SOCKET: OnceLock<OwnedFd> = OnceLock::new();
+
+    control_socket(&SOCKET, socket::AddressFamily::Inet6)
+}
@@ -17,6 +17,9 @@ nix::ioctl_write_int!(tunsetgroup, 'T', 206);
 // Can be used to attach or detach a mutliqueue.
 nix::ioctl_write_int!(tunsetqueue, 'T', 217);
 
+// Can be used to get the size of the virtio-net header prepended to the packets.
+nix::ioctl_read!(tungetvnethdrsz, 'T', 215, nix::libc::c_int);
+
 // Can be used to set and get the active flags of the device.
 nix::ioctl_write_ptr_bad!(siocsifflags, nix::libc::SIOCSIFFLAGS, bindings::ifreq);
 nix::ioctl_read_bad!(siocgifflags, nix::libc::SIOCGIFFLAGS, bindings::ifreq);
@@ -49,5 +52,8 @@ nix::ioctl_read_bad!(siocgifmetric, nix::libc::SIOCGIFMETRIC, bindings::ifreq);
 // Can be used to get the device index.
 nix::ioctl_read_bad!(siocgifindex, bindings::SIOCGIFINDEX, bindings::ifreq);
 
+// Can be used to get the device name from its index.
+nix::ioctl_read_bad!(siocgifname, nix::libc::SIOCGIFNAME, bindings::ifreq);
+
 // Can be used to delete an IPv6 address of the device.
 nix::ioctl_write_ptr_bad!(siocdifaddr6, bindings::SIOCDIFADDR, bindings::in6_ifreq);
@@ -19,13 +19,32 @@
 mod bindings;
 
 mod ioctl;
+mod netlink;
 mod sockaddr;
 
 mod common;
 pub use common::Mode;
 
+mod name;
+pub use name::InterfaceName;
+
+pub mod diagnostics;
+pub use diagnostics::diagnose;
+
 pub mod error;
+pub mod events;
 pub mod flags;
+pub mod neighbor;
+pub mod route;
+pub mod rule;
+pub mod stats;
+pub mod sysctl;
+
+mod handle;
+pub use handle::*;
+
+mod packet;
+pub use packet::*;
 
 mod device;
 pub use device::*;
@@ -33,7 +52,41 @@ pub use device::*;
 mod multiq;
 pub use multiq::*;
 
+mod steering;
+
+mod workers;
+pub use workers::*;
+
+mod list;
+pub use list::*;
+
 #[cfg(feature = "tokio")]
 mod asyncd;
 #[cfg(feature = "tokio")]
 pub use asyncd::*;
+
+#[cfg(feature = "mio")]
+mod event_source;
+
+#[cfg(feature = "async-io")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async-io")))]
+pub mod async_io;
+
+#[cfg(feature = "io-uring")]
+#[cfg_attr(docsrs, doc(cfg(feature = "io-uring")))]
+pub mod uring;
+
+#[cfg(feature = "tokio")]
+mod asyncmq;
+#[cfg(feature = "tokio")]
+pub use asyncmq::*;
+
+#[cfg(feature = "tokio")]
+mod split;
+#[cfg(feature = "tokio")]
+pub use split::*;
+
+#[cfg(feature = "tokio")]
+mod stream;
+#[cfg(feature = "tokio")]
+pub use stream::*;
@@ -0,0 +1,200 @@
+use std::os::unix::prelude::*;
+
+use nix::errno::Errno;
+
+use crate::common::{open_queue, Mode};
+use crate::error::{Context, Error, Result};
+use crate::flags::TunFlags;
+use crate::netlink::{self, Attrs, Message, Netlink};
+use crate::{bindings, ioctl, InterfaceName};
+
+// Attributes of the link info of a TUN/TAP device. Source: `linux/if_link.h`.
+const IFLA_INFO_DATA: u16 = 2;
+const IFLA_TUN_OWNER: u16 = 1;
+const IFLA_TUN_GROUP: u16 = 2;
+const IFLA_TUN_TYPE: u16 = 3;
+const IFLA_TUN_PI: u16 = 4;
+const IFLA_TUN_VNET_HDR: u16 = 5;
+const IFLA_TUN_PERSIST: u16 = 6;
+const IFLA_TUN_MULTI_QUEUE: u16 = 7;
+const IFLA_TUN_NUM_QUEUES: u16 = 8;
+const IFLA_TUN_NUM_DISABLED_QUEUES: u16 = 9;
+
+/// Represents a TUN/TAP interface which exists on the system.
+///
+/// Can be obtained using [`list_interfaces`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TunInterface {
+    /// Name of the interface.
+    pub name: String,
+
+    /// Index of the interface.
+    pub index: i32,
+
+    /// Mode of the interface.
+    pub mode: Mode,
+
+    /// Flags the interface was created with.
+    pub flags: TunFlags,
+
+    /// Id of the user who is allowed to attach to the interface, if any.
+    pub owner: Option<u32>,
+
+    /// Id of the group whose users are allowed to attach to the interface, if any.
+    pub group: Option<u32>,
+
+    /// Number of queues attached to the interface.
+    ///
+    /// Only reported for multi-queue interfaces.
+    pub queues: Option<u32>,
+
+    /// Number of queues which are opened but detached from the interface.
+    ///
+    /// Only reported for multi-queue interfaces.
+    pub disabled_queues: Option<u32>,
+}
+
+impl TunInterface {
+    /// Returns whether the interface outlives the file descriptors attached to it.
+    pub fn is_persistent(&self) -> bool {
+        self.flags.contains(TunFlags::IFF_PERSIST)
+    }
+
+    // Parses a `RTM_NEWLINK` message. Returns `None` if the link is not a TUN/TAP interface.
+    fn from_response(response: &netlink::Response) -> Option<Self> {
+        let ifinfomsg: bindings::ifinfomsg = response.header()?;
+
+        let mut name = None;
+        let mut is_tun = false;
+        let mut info_data = None;
+        for (ty, payload) in response.attrs::<bindings::ifinfomsg>() {
+            match ty {
+                nix::libc::IFLA_IFNAME => name = Some(netlink::to_string(payload)),
+                nix::libc::IFLA_LINKINFO => {
+                    for (ty, payload) in Attrs::nested(payload) {
+                        match ty {
+                            nix::libc::IFLA_INFO_KIND => {
+                                is_tun = netlink::to_string(payload) == "tun"
+                            }
+                            IFLA_INFO_DATA => info_data = Some(payload),
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if !is_tun {
+            return None;
+        }
+
+        let mut interface = TunInterface {
+            name: name?,
+            index: ifinfomsg.ifi_index,
+            mode: Mode::Tun,
+            flags: TunFlags::empty(),
+            owner: None,
+            group: None,
+            queues: None,
+            disabled_queues: None,
+        };
+
+        let mut packet_info = false;
+        for (ty, payload) in Attrs::nested(info_data?) {
+            let is_set = payload.first().is_some_and(|value| *value != 0);
+
+            match ty {
+                IFLA_TUN_OWNER => interface.owner = netlink::to_u32(payload),
+                IFLA_TUN_GROUP => interface.group = netlink::to_u32(payload),
+                IFLA_TUN_TYPE if payload.first() == Some(&(nix::libc::IFF_TAP as u8)) => {
+                    interface.mode = Mode::Tap
+                }
+                IFLA_TUN_PI => packet_info = is_set,
+                IFLA_TUN_VNET_HDR => interface.flags.set(TunFlags::IFF_VNET_HDR, is_set),
+                IFLA_TUN_PERSIST => interface.flags.set(TunFlags::IFF_PERSIST, is_set),
+                IFLA_TUN_MULTI_QUEUE => interface.flags.set(TunFlags::IFF_MULTI_QUEUE, is_set),
+                IFLA_TUN_NUM_QUEUES => interface.queues = netlink::to_u32(payload),
+                IFLA_TUN_NUM_DISABLED_QUEUES => {
+                    interface.disabled_queues = netlink::to_u32(payload)
+                }
+                _ => {}
+            }
+        }
+
+        interface.flags.set(TunFlags::IFF_NO_PI, !packet_info);
+        interface.flags |= match interface.mode {
+            Mode::Tun => TunFlags::IFF_TUN,
+            Mode::Tap => TunFlags::IFF_TAP,
+        };
+
+        Some(interface)
+    }
+}
+
+/// Returns all TUN/TAP interfaces of the system, including the persistent ones
+/// which are not attached to any process.
+///
+/// **NOTE**: Requires Linux 4.15 or newer.
+pub fn list_interfaces() -> Result<Vec<TunInterface>> {
+    let ifinfomsg: bindings::ifinfomsg = unsafe { std::mem::zeroed() };
+    let msg = Message::new(nix::libc::RTM_GETLINK, 0, &ifinfomsg);
+
+    Ok(Netlink::new()?
+        .dump(msg)
+        .op_context("RTM_GETLINK")?
+        .iter()
+        .filter_map(TunInterface::from_response)
+        .collect())
+}
+
+/// Destroys the TUN/TAP interface called `name`, which is usually a persistent interface left
+/// behind by a process.
+///
+/// The interface is attached to, made non-persistent, and then closed, which makes the kernel
+/// destroy it.
+///
+/// # Returns
+/// * `Ok`: If the interface was destroyed.
+/// * `Err(Error::DeviceNotFound)`: If there is no interface called `name`.
+/// * `Err(Error::NotTunTap)`: If the interface is not a TUN/TAP interface.
+/// * `Err(Error::DeviceBusy)`: If a process has the interface open.
+/// * `Err`: If any of the other steps failed.
+pub fn delete_device(name: impl AsRef<str>) -> Result<()> {
+    let name = InterfaceName::new(name.as_ref())?;
+
+    let file = match open_queue(&name, false) {
+        Err(err) if err.errno() == Some(Errno::EBUSY) => {
+            return Err(Error::DeviceBusy(name.to_string()))
+        }
+        result => result?,
+    };
+
+    // Attaching to a multiqueue interface succeeds while other queues are open, so check them
+    // once attached, which also catches the queues attached after looking the interface up.
+    let interface = find_interface(&name)?;
+    if interface.queues.unwrap_or_default() + interface.disabled_queues.unwrap_or_default() > 1 {
+        return Err(Error::DeviceBusy(name.to_string()));
+    }
+
+    unsafe { ioctl::tunsetpersist(file.as_raw_fd(), 0) }.context("TUNSETPERSIST", &name)?;
+
+    // The interface is destroyed once its last file descriptor is closed.
+    drop(file);
+
+    Ok(())
+}
+
+// Returns the TUN/TAP interface called `name`.
+pub(crate) fn find_interface(name: &str) -> Result<TunInterface> {
+    let ifinfomsg: bindings::ifinfomsg = unsafe { std::mem::zeroed() };
+    let msg =
+        Message::new(nix::libc::RTM_GETLINK, 0, &ifinfomsg).attr_str(nix::libc::IFLA_IFNAME, name);
+
+    let response = match Netlink::new()?.get(msg) {
+        Err(Error::NixError(Errno::ENODEV)) => return Err(Error::DeviceNotFound(name.to_string())),
+        result => result.context("RTM_GETLINK", name)?,
+    };
+
+    TunInterface::from_response(&response).ok_or_else(|| Error::NotTunTap(name.to_string()))
+}
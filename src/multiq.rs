@@ -1,81 +1,359 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::ops;
 use std::os::unix::prelude::AsRawFd;
+use std::sync::{Arc, Mutex, MutexGuard};
 
-use crate::common::create_device;
+use nix::fcntl::{fcntl, FcntlArg, OFlag};
+
+use crate::common::{create_device, open_queue};
 use crate::device::Device;
-use crate::error::{Error, Result};
-use crate::{bindings, ioctl, Mode};
+use crate::error::{Context, Error, Result};
+use crate::list::find_interface;
+use crate::{bindings, ioctl, steering, InterfaceHandle, Mode};
 
 /// Represents a multiqueue TUN/TAP device.
 ///
 /// Contains the shared code between [`MQTun`](crate::MQTun) and [`MQTap`](crate::MQTap).
 #[derive(Debug)]
-pub struct MQDevice(Device);
+pub struct MQDevice {
+    // Closed before the slot is dropped, since the queue is only removed from the interface
+    // once all of its file descriptors are closed.
+    device: Device,
+    slot: Arc<QueueSlot>,
+}
 impl MQDevice {
     fn new(
         name: impl AsRef<str>,
         mode: Mode,
         device_count: usize,
         packet_info: bool,
+        non_blocking: bool,
     ) -> Result<impl Iterator<Item = Self>> {
         if device_count == 0 {
             return Err(Error::ZeroDevices);
         }
 
-        let (name, files, inet4_socket, inet6_socket) =
-            create_device(name, mode, device_count, packet_info, false)?;
+        let (handle, files) =
+            create_device(name, mode, device_count, packet_info, non_blocking, true)?;
+        let slots = QueueSlot::new_all(&handle, device_count);
 
         Ok(files
             .into_iter()
-            .map(move |file| Device {
-                name: name.clone(),
-                file,
-                inet4_socket: inet4_socket.clone(),
-                inet6_socket: inet6_socket.clone(),
-            })
-            .map(MQDevice))
+            .zip(slots)
+            .map(move |(file, slot)| MQDevice {
+                device: Device {
+                    handle: handle.clone(),
+                    file,
+                },
+                slot,
+            }))
     }
 
     /// Attaches the multiqueue.
     ///
     /// # Returns
     /// * `Ok`: If attaching was succesful.
+    /// * `Err(Error::SteeringNotRestored)`: If the queue was attached, but the steering
+    ///   settings of the queues couldn't be moved to their indices.
     /// * `Err`: Otherwise.
     pub fn attach(&self) -> Result<()> {
-        let mut ifreq: bindings::ifreq = unsafe { std::mem::zeroed() };
-
-        ifreq.ifr_ifru.ifru_flags = nix::libc::IFF_ATTACH_QUEUE as i16;
-
-        unsafe { ioctl::tunsetqueue(self.as_raw_fd(), &ifreq as *const bindings::ifreq as u64)? };
-
-        Ok(())
+        self.slot.attach(&self.device)
     }
 
     /// Detaches the multiqueue.
     ///
     /// # Returns
     /// * `Ok`: If detaching was succesful.
+    /// * `Err(Error::SteeringNotRestored)`: If the queue was detached, but the steering
+    ///   settings of the queues couldn't be moved to their indices.
     /// * `Err`: Otherwise.
     pub fn detach(&self) -> Result<()> {
-        let mut ifreq: bindings::ifreq = unsafe { std::mem::zeroed() };
+        self.slot.detach(&self.device)
+    }
+
+    /// Opens another queue of the interface, e.g. for adding a worker as the load increases.
+    ///
+    /// The new queue is attached right away, and is non-blocking if this queue is. A queue is
+    /// removed from the interface when it's dropped.
+    pub fn add_queue(&self) -> Result<MQDevice> {
+        let (device, slot) = self.slot.add_queue(&self.device)?;
+
+        Ok(MQDevice { device, slot })
+    }
+
+    /// Creates a new handle to the same queue, e.g. for reading and writing it from different
+    /// threads.
+    ///
+    /// The queue is removed from the interface once all of its handles are dropped.
+    /// [`Device::try_clone`] can't tell which queue it duplicates, so the indices of the
+    /// queues go out of sync if a duplicate made using it outlives this queue.
+    pub fn try_clone(&self) -> Result<MQDevice> {
+        Ok(MQDevice {
+            device: self.device.try_clone()?,
+            slot: self.slot.clone(),
+        })
+    }
+
+    /// Returns the number of queues attached to the interface, which doesn't include the
+    /// detached queues.
+    pub fn queue_count(&self) -> Result<usize> {
+        queue_count(self)
+    }
+
+    /// Returns the index of the queue in the interface, or `None` if it's detached.
+    ///
+    /// The kernel reindexes the queues when one of them is detached or dropped: the last
+    /// queue takes the index of the removed one. The index is tracked across the queues
+    /// created together, the ones added using [`add_queue`](Self::add_queue) and their
+    /// clones made using [`try_clone`](Self::try_clone), so it's only accurate if no other
+    /// file descriptor of the interface is attached, detached or closed.
+    pub fn queue_index(&self) -> Option<usize> {
+        self.slot.index()
+    }
+
+    pub(crate) fn slot(&self) -> &QueueSlot {
+        &self.slot
+    }
+}
+
+// The attached queues of an interface in the order of their indices in the kernel, along
+// with the sysfs settings of the queues, which belong to the indices.
+#[derive(Debug)]
+pub(crate) struct Queues {
+    handle: InterfaceHandle,
+    next_id: usize,
+    attached: Vec<usize>,
+    // Sysfs files of each queue set through the registry, by (`tx`/`rx`, file name).
+    settings: HashMap<usize, BTreeMap<(&'static str, &'static str), String>>,
+    // Queues whose settings weren't written to their current index yet.
+    stale: BTreeSet<usize>,
+}
+
+impl Queues {
+    pub(crate) fn index(&self, id: usize) -> Option<usize> {
+        self.attached.iter().position(|attached| *attached == id)
+    }
+
+    // Adds the queue `id` at the end, like the kernel does when a queue is attached. Its
+    // settings are left for `restore`.
+    fn push(&mut self, id: usize) {
+        self.attached.push(id);
+        self.stale.insert(id);
+    }
 
-        ifreq.ifr_ifru.ifru_flags = nix::libc::IFF_DETACH_QUEUE as i16;
+    // Mirrors the kernel, which moves the last queue into the index of the removed queue.
+    // The settings of the moved queue are left for `restore`.
+    fn remove(&mut self, id: usize) {
+        if let Some(index) = self.index(id) {
+            self.attached.swap_remove(index);
 
-        unsafe { ioctl::tunsetqueue(self.as_raw_fd(), &ifreq as *const bindings::ifreq as u64)? };
+            if let Some(&moved) = self.attached.get(index) {
+                self.stale.insert(moved);
+            }
+        }
+    }
+
+    // Writes the settings of the queues which were moved to another index. The ones which
+    // fail are tried again on the next call.
+    fn restore(&mut self) -> Result<()> {
+        let mut result = Ok(());
+
+        for id in std::mem::take(&mut self.stale) {
+            if let Err(err) = self.apply(id) {
+                self.stale.insert(id);
+                result = result.and(Err(err));
+            }
+        }
+
+        result.map_err(|err| {
+            Error::SteeringNotRestored(self.handle.name().to_string(), Box::new(err))
+        })
+    }
+
+    // Writes `value` to the sysfs file `name` of the queue `id` in the `dir` direction, and
+    // remembers it for when the queue is reindexed.
+    pub(crate) fn set(
+        &mut self,
+        id: usize,
+        dir: &'static str,
+        name: &'static str,
+        value: String,
+    ) -> Result<()> {
+        let index = self
+            .index(id)
+            .ok_or_else(|| Error::QueueDetached(self.handle.name().to_string()))?;
+
+        steering::write_sysfs(&self.handle, index, dir, name, &value)?;
+        self.settings
+            .entry(id)
+            .or_default()
+            .insert((dir, name), value);
+
+        Ok(())
+    }
+
+    // Reads the sysfs file `name` of the queue `id` in the `dir` direction.
+    pub(crate) fn get(&self, id: usize, dir: &str, name: &str) -> Result<(String, String)> {
+        let index = self
+            .index(id)
+            .ok_or_else(|| Error::QueueDetached(self.handle.name().to_string()))?;
+
+        steering::read_sysfs(&self.handle, index, dir, name)
+    }
+
+    // Writes the remembered settings of the queue `id` to its current index.
+    fn apply(&self, id: usize) -> Result<()> {
+        let (index, settings) = match (self.index(id), self.settings.get(&id)) {
+            (Some(index), Some(settings)) => (index, settings),
+            _ => return Ok(()),
+        };
+
+        for ((dir, name), value) in settings {
+            steering::write_sysfs(&self.handle, index, dir, name, value)?;
+        }
 
         Ok(())
     }
 }
+
+// The membership of a queue in the registry of its interface, shared by the handles of the
+// queue. Removes the queue from the registry once all of them are dropped, like the kernel
+// does once their file descriptors are closed.
+#[derive(Debug)]
+pub(crate) struct QueueSlot {
+    id: usize,
+    queues: Arc<Mutex<Queues>>,
+}
+
+impl QueueSlot {
+    // Creates the slots of `count` queues which were attached in order, so their indices are
+    // their positions.
+    pub(crate) fn new_all(handle: &InterfaceHandle, count: usize) -> Vec<Arc<QueueSlot>> {
+        let queues = Arc::new(Mutex::new(Queues {
+            handle: handle.clone(),
+            next_id: count,
+            attached: (0..count).collect(),
+            settings: HashMap::new(),
+            stale: BTreeSet::new(),
+        }));
+
+        (0..count)
+            .map(|id| {
+                Arc::new(QueueSlot {
+                    id,
+                    queues: queues.clone(),
+                })
+            })
+            .collect()
+    }
+
+    // Attaches the queue of `device`, which belongs to this slot.
+    pub(crate) fn attach(&self, device: &Device) -> Result<()> {
+        let mut queues = self.lock();
+
+        set_queue(device, true)?;
+        queues.push(self.id);
+
+        queues.restore()
+    }
+
+    // Detaches the queue of `device`, which belongs to this slot.
+    pub(crate) fn detach(&self, device: &Device) -> Result<()> {
+        let mut queues = self.lock();
+
+        set_queue(device, false)?;
+        queues.remove(self.id);
+
+        queues.restore()
+    }
+
+    // Opens another queue of the interface of `device`, which belongs to this slot.
+    pub(crate) fn add_queue(&self, device: &Device) -> Result<(Device, Arc<QueueSlot>)> {
+        let mut queues = self.lock();
+
+        let device = new_queue(device)?;
+        let id = queues.next_id;
+        queues.next_id += 1;
+        // A new queue has no settings, so there's nothing to restore.
+        queues.attached.push(id);
+
+        let slot = Arc::new(QueueSlot {
+            id,
+            queues: self.queues.clone(),
+        });
+
+        Ok((device, slot))
+    }
+
+    pub(crate) fn index(&self) -> Option<usize> {
+        self.lock().index(self.id)
+    }
+
+    pub(crate) fn id(&self) -> usize {
+        self.id
+    }
+
+    pub(crate) fn lock(&self) -> MutexGuard<'_, Queues> {
+        self.queues.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Drop for QueueSlot {
+    fn drop(&mut self) {
+        let mut queues = self.lock();
+
+        queues.settings.remove(&self.id);
+        queues.stale.remove(&self.id);
+        queues.remove(self.id);
+        // The settings which failed to move are reported by the next attach or detach.
+        let _ = queues.restore();
+    }
+}
+
+// Opens another queue of the interface of `device`, which is non-blocking if `device` is.
+pub(crate) fn new_queue(device: &Device) -> Result<Device> {
+    let flags = fcntl(device.as_raw_fd(), FcntlArg::F_GETFL).context("fcntl", &device.name)?;
+    let non_blocking = OFlag::from_bits_truncate(flags).contains(OFlag::O_NONBLOCK);
+
+    Ok(Device {
+        handle: device.handle.clone(),
+        file: open_queue(&device.current_name()?, non_blocking)?,
+    })
+}
+
+// Returns the number of queues attached to the interface of `device`.
+pub(crate) fn queue_count(device: &Device) -> Result<usize> {
+    let interface = find_interface(&device.current_name()?)?;
+
+    Ok(interface.queues.unwrap_or(0) as usize)
+}
+
+// Attaches the queue of `device` to its interface, or detaches it.
+pub(crate) fn set_queue(device: &Device, attach: bool) -> Result<()> {
+    let mut ifreq: bindings::ifreq = unsafe { std::mem::zeroed() };
+
+    ifreq.ifr_ifru.ifru_flags = if attach {
+        nix::libc::IFF_ATTACH_QUEUE
+    } else {
+        nix::libc::IFF_DETACH_QUEUE
+    } as i16;
+
+    unsafe { ioctl::tunsetqueue(device.as_raw_fd(), &ifreq as *const bindings::ifreq as u64) }
+        .context("TUNSETQUEUE", &device.name)?;
+
+    Ok(())
+}
+
 impl ops::Deref for MQDevice {
     type Target = Device;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.device
     }
 }
 impl ops::DerefMut for MQDevice {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.device
     }
 }
 
@@ -84,10 +362,36 @@ impl ops::DerefMut for MQDevice {
 pub struct MQTun(MQDevice);
 impl MQTun {
     pub fn new(name: impl AsRef<str>, device_count: usize, packet_info: bool) -> Result<Vec<Self>> {
-        let devices = MQDevice::new(name, Mode::Tun, device_count, packet_info)?;
+        let devices = MQDevice::new(name, Mode::Tun, device_count, packet_info, false)?;
 
         Ok(devices.map(MQTun).collect())
     }
+
+    /// Creates a multiqueue TUN device whose queues are non-blocking, e.g. for registering
+    /// them with an event loop.
+    pub fn new_nonblocking(
+        name: impl AsRef<str>,
+        device_count: usize,
+        packet_info: bool,
+    ) -> Result<Vec<Self>> {
+        let devices = MQDevice::new(name, Mode::Tun, device_count, packet_info, true)?;
+
+        Ok(devices.map(MQTun).collect())
+    }
+
+    /// Opens another queue of the interface.
+    ///
+    /// See [`MQDevice::add_queue`].
+    pub fn add_queue(&self) -> Result<Self> {
+        self.0.add_queue().map(MQTun)
+    }
+
+    /// Creates a new handle to the same queue.
+    ///
+    /// See [`MQDevice::try_clone`].
+    pub fn try_clone(&self) -> Result<Self> {
+        self.0.try_clone().map(MQTun)
+    }
 }
 impl ops::Deref for MQTun {
     type Target = MQDevice;
@@ -107,10 +411,36 @@ impl ops::DerefMut for MQTun {
 pub struct MQTap(MQDevice);
 impl MQTap {
     pub fn new(name: impl AsRef<str>, device_count: usize, packet_info: bool) -> Result<Vec<Self>> {
-        let devices = MQDevice::new(name, Mode::Tap, device_count, packet_info)?;
+        let devices = MQDevice::new(name, Mode::Tap, device_count, packet_info, false)?;
+
+        Ok(devices.map(MQTap).collect())
+    }
+
+    /// Creates a multiqueue TAP device whose queues are non-blocking, e.g. for registering
+    /// them with an event loop.
+    pub fn new_nonblocking(
+        name: impl AsRef<str>,
+        device_count: usize,
+        packet_info: bool,
+    ) -> Result<Vec<Self>> {
+        let devices = MQDevice::new(name, Mode::Tap, device_count, packet_info, true)?;
 
         Ok(devices.map(MQTap).collect())
     }
+
+    /// Opens another queue of the interface.
+    ///
+    /// See [`MQDevice::add_queue`].
+    pub fn add_queue(&self) -> Result<Self> {
+        self.0.add_queue().map(MQTap)
+    }
+
+    /// Creates a new handle to the same queue.
+    ///
+    /// See [`MQDevice::try_clone`].
+    pub fn try_clone(&self) -> Result<Self> {
+        self.0.try_clone().map(MQTap)
+    }
 }
 impl ops::Deref for MQTap {
     type Target = MQDevice;
@@ -0,0 +1,165 @@
+use std::{fmt, ops, str};
+
+use crate::error::{Error, Result};
+
+// Maximum length of an interface name including the null terminator.
+//
+// Source: The IFNAMSIZ is defined in the `linux/if.h`.
+const IFNAMSIZ: usize = 16;
+
+/// Represents a valid name of a network interface.
+///
+/// The kernel accepts a name if:
+/// * It is not empty and is at most 15 bytes long.
+/// * It is not `.` or `..`.
+/// * It doesn't contain `/`, `:` or whitespace characters.
+///
+/// A name can also be a template containing a single `%d`, e.g. `tun%d`. In that case, the
+/// kernel replaces `%d` with the smallest number which makes the name unique. The chosen name
+/// can be obtained from the created device using [`Device::name`](crate::Device::name).
+///
+/// For more info: `dev_valid_name` and `__dev_alloc_name` in `net/core/dev.c`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InterfaceName(String);
+
+impl InterfaceName {
+    /// Validates `name` and wraps it.
+    ///
+    /// # Returns
+    /// * `Ok`: If `name` is accepted by the kernel.
+    /// * `Err(Error::InvalidInterfaceName)`: Otherwise, containing the reason.
+    pub fn new(name: impl Into<String>) -> Result<Self> {
+        let name = name.into();
+
+        if let Err(reason) = validate(&name) {
+            return Err(Error::InvalidInterfaceName(name, reason));
+        }
+
+        Ok(InterfaceName(name))
+    }
+
+    /// Returns whether the name is a template which is completed by the kernel.
+    pub fn is_template(&self) -> bool {
+        self.0.contains("%d")
+    }
+
+    /// Returns the name as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    // Returns the name in the null terminated format the kernel expects.
+    pub(crate) fn to_ifr_name(&self) -> [i8; IFNAMSIZ] {
+        let mut ifr_name = [0i8; IFNAMSIZ];
+        for (i, c) in self.0.as_bytes().iter().enumerate() {
+            ifr_name[i] = *c as i8;
+        }
+
+        ifr_name
+    }
+
+    // Reads the name the kernel wrote into an ifreq.
+    pub(crate) fn from_ifr_name(ifr_name: &[i8; IFNAMSIZ]) -> Self {
+        let bytes: Vec<u8> = ifr_name
+            .iter()
+            .map_while(|c| if *c != 0 { Some(*c as u8) } else { None })
+            .collect();
+
+        InterfaceName(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+// Returns the reason `name` is rejected by the kernel, if any.
+fn validate(name: &str) -> std::result::Result<(), &'static str> {
+    if name.is_empty() {
+        return Err("name is empty");
+    }
+
+    if name.len() >= IFNAMSIZ {
+        return Err("name is longer than 15 bytes");
+    }
+
+    if name == "." || name == ".." {
+        return Err("name can't be `.` or `..`");
+    }
+
+    if name.contains(|c: char| c == '/' || c == ':' || c.is_whitespace()) {
+        return Err("name contains `/`, `:` or whitespace");
+    }
+
+    if let Some((_, rest)) = name.split_once('%') {
+        if !rest.starts_with('d') || rest.contains('%') {
+            return Err("name can only contain a single `%d` template");
+        }
+    }
+
+    Ok(())
+}
+
+impl str::FromStr for InterfaceName {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        InterfaceName::new(name)
+    }
+}
+
+impl TryFrom<&str> for InterfaceName {
+    type Error = Error;
+
+    fn try_from(name: &str) -> Result<Self> {
+        InterfaceName::new(name)
+    }
+}
+
+impl TryFrom<String> for InterfaceName {
+    type Error = Error;
+
+    fn try_from(name: String) -> Result<Self> {
+        InterfaceName::new(name)
+    }
+}
+
+impl From<InterfaceName> for String {
+    fn from(name: InterfaceName) -> Self {
+        name.0
+    }
+}
+
+impl fmt::Display for InterfaceName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl ops::Deref for InterfaceName {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<str> for InterfaceName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for InterfaceName {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for InterfaceName {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<String> for InterfaceName {
+    fn eq(&self, other: &String) -> bool {
+        &self.0 == other
+    }
+}
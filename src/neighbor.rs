@@ -0,0 +1,182 @@
+//! Neighbor (ARP/NDP) entries of a TUN/TAP device.
+
+use std::net;
+
+use crate::bindings;
+use crate::error::{Context, Result};
+use crate::handle::InterfaceHandle;
+use crate::netlink::{self, Message, Netlink};
+
+/// State of a neighbor entry.
+///
+/// For more info: `man ip-neighbour`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NeighborState {
+    /// The entry is valid forever and can only be removed administratively.
+    Permanent,
+
+    /// The entry is valid until its reachability timeout expires.
+    Reachable,
+
+    /// The entry is valid but suspicious.
+    Stale,
+
+    /// The entry is valid but its validation is delayed.
+    Delay,
+
+    /// The neighbor is being probed.
+    Probe,
+
+    /// Address resolution is in progress.
+    Incomplete,
+
+    /// The maximum number of probes was exceeded without success.
+    Failed,
+
+    /// The entry is valid and no attempts to validate it are made.
+    NoArp,
+
+    /// The entry is being created or is about to be removed.
+    None,
+}
+
+impl NeighborState {
+    fn to_raw(self) -> u16 {
+        match self {
+            NeighborState::Permanent => nix::libc::NUD_PERMANENT,
+            NeighborState::Reachable => nix::libc::NUD_REACHABLE,
+            NeighborState::Stale => nix::libc::NUD_STALE,
+            NeighborState::Delay => nix::libc::NUD_DELAY,
+            NeighborState::Probe => nix::libc::NUD_PROBE,
+            NeighborState::Incomplete => nix::libc::NUD_INCOMPLETE,
+            NeighborState::Failed => nix::libc::NUD_FAILED,
+            NeighborState::NoArp => nix::libc::NUD_NOARP,
+            NeighborState::None => nix::libc::NUD_NONE,
+        }
+    }
+
+    fn from_raw(state: u16) -> Option<Self> {
+        Some(match state {
+            nix::libc::NUD_PERMANENT => NeighborState::Permanent,
+            nix::libc::NUD_REACHABLE => NeighborState::Reachable,
+            nix::libc::NUD_STALE => NeighborState::Stale,
+            nix::libc::NUD_DELAY => NeighborState::Delay,
+            nix::libc::NUD_PROBE => NeighborState::Probe,
+            nix::libc::NUD_INCOMPLETE => NeighborState::Incomplete,
+            nix::libc::NUD_FAILED => NeighborState::Failed,
+            nix::libc::NUD_NOARP => NeighborState::NoArp,
+            nix::libc::NUD_NONE => NeighborState::None,
+            _ => return None,
+        })
+    }
+}
+
+/// Represents an entry of the neighbor table, which maps an IPv4 (ARP) or IPv6 (NDP)
+/// address to a MAC address.
+///
+/// Neighbors are managed through the device using [`InterfaceHandle::add_neighbor`],
+/// [`InterfaceHandle::del_neighbor`] and [`InterfaceHandle::get_neighbors`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Neighbor {
+    /// IP address of the neighbor.
+    pub addr: net::IpAddr,
+
+    /// MAC address of the neighbor. It's `None` if the address is not resolved yet.
+    pub lladdr: Option<[u8; 6]>,
+
+    /// State of the entry.
+    pub state: NeighborState,
+}
+
+impl Neighbor {
+    /// Creates a permanent entry mapping `addr` to `lladdr`.
+    pub fn new(addr: impl Into<net::IpAddr>, lladdr: [u8; 6]) -> Self {
+        Neighbor {
+            addr: addr.into(),
+            lladdr: Some(lladdr),
+            state: NeighborState::Permanent,
+        }
+    }
+
+    // Builds a `RTM_NEWNEIGH`/`RTM_DELNEIGH` message for this entry on `ifindex`.
+    fn to_message(&self, ty: u16, flags: i32, ifindex: i32) -> Message {
+        let mut ndmsg: bindings::ndmsg = unsafe { std::mem::zeroed() };
+        ndmsg.ndm_family = netlink::family(&self.addr);
+        ndmsg.ndm_ifindex = ifindex;
+        ndmsg.ndm_state = self.state.to_raw();
+
+        let mut msg = Message::new(ty, flags, &ndmsg)
+            .attr(nix::libc::NDA_DST, &netlink::ip_octets(&self.addr));
+
+        if let Some(lladdr) = &self.lladdr {
+            msg = msg.attr(nix::libc::NDA_LLADDR, lladdr);
+        }
+
+        msg
+    }
+
+    // Parses an entry from a `RTM_NEWNEIGH` message and returns it with the index of its interface.
+    fn from_response(response: &netlink::Response) -> Option<(Self, i32)> {
+        let ndmsg: bindings::ndmsg = response.header()?;
+
+        let mut addr = None;
+        let mut lladdr = None;
+        for (ty, payload) in response.attrs::<bindings::ndmsg>() {
+            match ty {
+                nix::libc::NDA_DST => addr = netlink::to_ip(payload),
+                nix::libc::NDA_LLADDR => lladdr = payload.try_into().ok(),
+                _ => {}
+            }
+        }
+
+        let neighbor = Neighbor {
+            addr: addr?,
+            lladdr,
+            state: NeighborState::from_raw(ndmsg.ndm_state)?,
+        };
+
+        Some((neighbor, ndmsg.ndm_ifindex))
+    }
+}
+
+impl InterfaceHandle {
+    /// Adds `neighbor` to the neighbor table of the device.
+    pub fn add_neighbor(&self, neighbor: &Neighbor) -> Result<()> {
+        let msg = neighbor.to_message(
+            nix::libc::RTM_NEWNEIGH,
+            nix::libc::NLM_F_CREATE | nix::libc::NLM_F_EXCL,
+            self.get_index()?,
+        );
+
+        Netlink::new()?
+            .request(msg)
+            .context("RTM_NEWNEIGH", &self.name)
+    }
+
+    /// Deletes the entry of `neighbor` from the neighbor table of the device.
+    pub fn del_neighbor(&self, neighbor: &Neighbor) -> Result<()> {
+        let msg = neighbor.to_message(nix::libc::RTM_DELNEIGH, 0, self.get_index()?);
+
+        Netlink::new()?
+            .request(msg)
+            .context("RTM_DELNEIGH", &self.name)
+    }
+
+    /// Returns the IPv4 and IPv6 entries of the neighbor table of the device.
+    pub fn get_neighbors(&self) -> Result<Vec<Neighbor>> {
+        let ifindex = self.get_index()?;
+
+        let ndmsg: bindings::ndmsg = unsafe { std::mem::zeroed() };
+        let msg = Message::new(nix::libc::RTM_GETNEIGH, 0, &ndmsg);
+
+        Ok(Netlink::new()?
+            .dump(msg)
+            .context("RTM_GETNEIGH", &self.name)?
+            .iter()
+            .filter_map(Neighbor::from_response)
+            // We are only interested in the entries of our interface.
+            .filter(|(_, index)| *index == ifindex)
+            .map(|(neighbor, _)| neighbor)
+            .collect())
+    }
+}
@@ -0,0 +1,392 @@
+// Some parts of the network configuration (routes, rules, neighbors, ...) can only
+// be managed through rtnetlink, so unlike the rest of the crate they can't be done
+// using ioctls. This module contains a minimal rtnetlink client which is just enough
+// for building requests, sending them to the kernel and parsing the replies.
+//
+// Every rtnetlink message has the following layout:
+//
+// +----------+---------+--------+---------+--------+---------+-----+
+// | nlmsghdr | padding | family | padding | rtattr | payload | ... |
+// |          |         | header |         |        | padding |     |
+// +----------+---------+--------+---------+--------+---------+-----+
+//
+// The family header depends on the type of the message(e.g. `rtmsg` for routes),
+// and it's followed by a list of attributes. Each attribute is a `rtattr` followed
+// by its payload. Every part of the message is padded to a multiple of 4 bytes.
+//
+// For more info: `man 7 netlink` and `man 7 rtnetlink`
+
+use std::cell::Cell;
+use std::os::unix::prelude::*;
+use std::{mem, net, ptr, slice};
+
+use nix::errno::Errno;
+use nix::sys::socket;
+
+use crate::bindings;
+use crate::error::{Context, Error, Result};
+
+const ALIGNTO: usize = 4;
+
+// Large enough to hold a whole datagram of a dump reply.
+const RECV_BUF_SIZE: usize = 64 * 1024;
+
+// Set on the messages of a dump which was interrupted by a concurrent change (`linux/netlink.h`).
+const NLM_F_DUMP_INTR: u16 = 0x10;
+
+// How many times a dump which was interrupted by a concurrent change is restarted.
+const DUMP_RETRIES: usize = 3;
+
+// The type of an attribute may contain the `NLA_F_NESTED` and `NLA_F_NET_BYTEORDER` flags.
+const NLA_TYPE_MASK: u16 = !(nix::libc::NLA_F_NESTED | nix::libc::NLA_F_NET_BYTEORDER) as u16;
+
+fn align(len: usize) -> usize {
+    (len + ALIGNTO - 1) & !(ALIGNTO - 1)
+}
+
+/// Returns the bytes of `value` to be used as the payload of an attribute.
+pub fn as_bytes<T: Copy>(value: &T) -> &[u8] {
+    // Safety:
+    //
+    // `T` is one of the plain `repr(C)` structs of the uapi, so viewing it as bytes is fine.
+    unsafe { slice::from_raw_parts(value as *const T as *const u8, mem::size_of::<T>()) }
+}
+
+/// Reads a `T` from the start of `bytes`, if there are enough bytes for it.
+pub fn read<T: Copy>(bytes: &[u8]) -> Option<T> {
+    if bytes.len() < mem::size_of::<T>() {
+        return None;
+    }
+
+    // Safety:
+    //
+    // We checked the length, and the data inside a netlink message is not necessarily aligned
+    // for `T`, hence the unaligned read.
+    Some(unsafe { ptr::read_unaligned(bytes.as_ptr() as *const T) })
+}
+
+/// Returns the octets of `addr` in the form the kernel expects them in an attribute.
+pub fn ip_octets(addr: &net::IpAddr) -> Vec<u8> {
+    match addr {
+        net::IpAddr::V4(addr) => addr.octets().to_vec(),
+        net::IpAddr::V6(addr) => addr.octets().to_vec(),
+    }
+}
+
+/// Converts the payload of an address attribute to an `IpAddr`.
+pub fn to_ip(bytes: &[u8]) -> Option<net::IpAddr> {
+    if let Ok(octets) = <[u8; 4]>::try_from(bytes) {
+        Some(net::Ipv4Addr::from(octets).into())
+    } else if let Ok(octets) = <[u8; 16]>::try_from(bytes) {
+        Some(net::Ipv6Addr::from(octets).into())
+    } else {
+        None
+    }
+}
+
+/// Converts the payload of an attribute to an `u32`.
+pub fn to_u32(bytes: &[u8]) -> Option<u32> {
+    Some(u32::from_ne_bytes(bytes.get(..4)?.try_into().ok()?))
+}
+
+/// Converts the payload of a string attribute to a `String`.
+pub fn to_string(bytes: &[u8]) -> String {
+    // Strings are null terminated inside the attributes.
+    let len = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+
+    String::from_utf8_lossy(&bytes[..len]).into_owned()
+}
+
+/// Returns the address family of `addr`.
+pub fn family(addr: &net::IpAddr) -> u8 {
+    match addr {
+        net::IpAddr::V4(_) => nix::libc::AF_INET as u8,
+        net::IpAddr::V6(_) => nix::libc::AF_INET6 as u8,
+    }
+}
+
+/// A request to be sent to the kernel.
+#[derive(Clone)]
+pub struct Message {
+    ty: u16,
+    flags: u16,
+    buf: Vec<u8>,
+}
+impl Message {
+    /// Creates a message of type `ty` with the family specific `header`.
+    pub fn new<T: Copy>(ty: u16, flags: i32, header: &T) -> Self {
+        let mut buf = vec![0; align(mem::size_of::<bindings::nlmsghdr>())];
+        buf.extend_from_slice(as_bytes(header));
+        buf.resize(align(buf.len()), 0);
+
+        Message {
+            ty,
+            flags: flags as u16,
+            buf,
+        }
+    }
+
+    /// Appends an attribute of type `ty` containing `payload` to the message.
+    pub fn attr(mut self, ty: u16, payload: &[u8]) -> Self {
+        let rtattr = bindings::rtattr {
+            rta_len: (mem::size_of::<bindings::rtattr>() + payload.len()) as u16,
+            rta_type: ty,
+        };
+
+        self.buf.extend_from_slice(as_bytes(&rtattr));
+        self.buf.extend_from_slice(payload);
+        self.buf.resize(align(self.buf.len()), 0);
+
+        self
+    }
+
+    /// Appends an attribute of type `ty` containing `value` to the message.
+    pub fn attr_u32(self, ty: u16, value: u32) -> Self {
+        self.attr(ty, &value.to_ne_bytes())
+    }
+
+    /// Appends a string attribute of type `ty` containing `value` to the message.
+    pub fn attr_str(self, ty: u16, value: &str) -> Self {
+        let mut payload = value.as_bytes().to_vec();
+        payload.push(0);
+
+        self.attr(ty, &payload)
+    }
+
+    // Fills the netlink header and returns the bytes to be sent.
+    fn finish(mut self, flags: i32, seq: u32) -> Vec<u8> {
+        let nlmsghdr = bindings::nlmsghdr {
+            nlmsg_len: self.buf.len() as u32,
+            nlmsg_type: self.ty,
+            nlmsg_flags: self.flags | flags as u16,
+            nlmsg_seq: seq,
+            nlmsg_pid: 0,
+        };
+
+        self.buf[..mem::size_of::<bindings::nlmsghdr>()].copy_from_slice(as_bytes(&nlmsghdr));
+
+        self.buf
+    }
+}
+
+/// A message received from the kernel.
+pub struct Response {
+    pub ty: u16,
+    flags: u16,
+    seq: u32,
+    payload: Vec<u8>,
+}
+impl Response {
+    /// Returns the family specific header of the message.
+    pub fn header<T: Copy>(&self) -> Option<T> {
+        read(&self.payload)
+    }
+
+    /// Returns the attributes which come after a family specific header of type `T`.
+    pub fn attrs<T: Copy>(&self) -> Attrs<'_> {
+        Attrs(
+            self.payload
+                .get(align(mem::size_of::<T>())..)
+                .unwrap_or_default(),
+        )
+    }
+
+    // Returns the error code of an `NLMSG_ERROR` or `NLMSG_DONE` message.
+    fn error(&self) -> Result<()> {
+        let code = read::<i32>(&self.payload).ok_or(Error::MalformedNetlinkMessage)?;
+
+        if code < 0 {
+            Err(Errno::from_i32(-code).into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Iterator over the attributes of a message as `(type, payload)` pairs.
+pub struct Attrs<'a>(&'a [u8]);
+impl<'a> Attrs<'a> {
+    /// Returns the attributes nested inside the `payload` of another attribute.
+    pub fn nested(payload: &'a [u8]) -> Self {
+        Attrs(payload)
+    }
+}
+impl<'a> Iterator for Attrs<'a> {
+    type Item = (u16, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rtattr: bindings::rtattr = read(self.0)?;
+        let len = rtattr.rta_len as usize;
+
+        if len < mem::size_of::<bindings::rtattr>() || len > self.0.len() {
+            return None;
+        }
+
+        let payload = &self.0[mem::size_of::<bindings::rtattr>()..len];
+        self.0 = self.0.get(align(len)..).unwrap_or_default();
+
+        Some((rtattr.rta_type & NLA_TYPE_MASK, payload))
+    }
+}
+
+/// A rtnetlink socket.
+#[derive(Debug)]
+pub struct Netlink {
+    fd: OwnedFd,
+    seq: Cell<u32>,
+}
+impl Netlink {
+    /// Opens a socket for sending requests to the kernel.
+    pub fn new() -> Result<Self> {
+        Self::bind(0, socket::SockFlag::empty())
+    }
+
+    /// Opens a socket which receives the notifications of the multicast `groups`.
+    pub fn bind(groups: u32, flags: socket::SockFlag) -> Result<Self> {
+        let fd = unsafe {
+            OwnedFd::from_raw_fd(
+                socket::socket(
+                    socket::AddressFamily::Netlink,
+                    socket::SockType::Raw,
+                    socket::SockFlag::SOCK_CLOEXEC | flags,
+                    socket::SockProtocol::NetlinkRoute,
+                )
+                .op_context("socket")?,
+            )
+        };
+
+        socket::bind(fd.as_raw_fd(), &socket::NetlinkAddr::new(0, groups)).op_context("bind")?;
+
+        Ok(Netlink {
+            fd,
+            seq: Cell::new(0),
+        })
+    }
+
+    /// Sends `msg` and waits for the kernel to acknowledge it.
+    pub fn request(&self, msg: Message) -> Result<()> {
+        let seq = self.send(msg, nix::libc::NLM_F_REQUEST | nix::libc::NLM_F_ACK)?;
+
+        loop {
+            for response in self.recv()? {
+                if response.seq == seq && response.ty == nix::libc::NLMSG_ERROR as u16 {
+                    return response.error();
+                }
+            }
+        }
+    }
+
+    /// Sends the get request `msg` and returns the message the kernel replied with.
+    pub fn get(&self, msg: Message) -> Result<Response> {
+        let seq = self.send(msg, nix::libc::NLM_F_REQUEST)?;
+
+        loop {
+            for response in self.recv()? {
+                if response.seq != seq {
+                    continue;
+                }
+
+                match response.ty as i32 {
+                    nix::libc::NLMSG_ERROR => response.error()?,
+                    nix::libc::NLMSG_NOOP => {}
+                    _ => return Ok(response),
+                }
+            }
+        }
+    }
+
+    /// Sends the dump request `msg` and returns all the messages the kernel replied with.
+    ///
+    /// The dump is restarted if it was interrupted by a concurrent change, since its result
+    /// may be inconsistent.
+    pub fn dump(&self, msg: Message) -> Result<Vec<Response>> {
+        for _ in 0..DUMP_RETRIES {
+            if let Some(responses) = self.try_dump(msg.clone())? {
+                return Ok(responses);
+            }
+        }
+
+        Err(Error::NetlinkDumpInterrupted)
+    }
+
+    // Dumps `msg` once. Returns `None` if the dump was interrupted.
+    fn try_dump(&self, msg: Message) -> Result<Option<Vec<Response>>> {
+        let seq = self.send(msg, nix::libc::NLM_F_REQUEST | nix::libc::NLM_F_DUMP)?;
+
+        let mut interrupted = false;
+        let mut responses = vec![];
+        loop {
+            for response in self.recv()? {
+                if response.seq != seq {
+                    continue;
+                }
+
+                interrupted |= response.flags & NLM_F_DUMP_INTR != 0;
+
+                match response.ty as i32 {
+                    nix::libc::NLMSG_DONE => {
+                        response.error()?;
+
+                        return Ok((!interrupted).then_some(responses));
+                    }
+                    nix::libc::NLMSG_ERROR => response.error()?,
+                    nix::libc::NLMSG_NOOP => {}
+                    _ => responses.push(response),
+                }
+            }
+        }
+    }
+
+    /// Receives a single datagram and returns the messages inside it.
+    pub fn recv(&self) -> Result<Vec<Response>> {
+        let mut buf = vec![0; RECV_BUF_SIZE];
+        // With `MSG_TRUNC`, the whole length of a datagram which didn't fit is returned.
+        let len = socket::recv(self.fd.as_raw_fd(), &mut buf, socket::MsgFlags::MSG_TRUNC)?;
+        if len > buf.len() {
+            return Err(Error::NetlinkMessageTruncated(len));
+        }
+
+        let hdr_len = mem::size_of::<bindings::nlmsghdr>();
+
+        let mut responses = vec![];
+        let mut rest = &buf[..len];
+        while let Some(nlmsghdr) = read::<bindings::nlmsghdr>(rest) {
+            let msg_len = nlmsghdr.nlmsg_len as usize;
+            if msg_len < hdr_len || msg_len > rest.len() {
+                return Err(Error::MalformedNetlinkMessage);
+            }
+
+            responses.push(Response {
+                ty: nlmsghdr.nlmsg_type,
+                flags: nlmsghdr.nlmsg_flags,
+                seq: nlmsghdr.nlmsg_seq,
+                payload: rest[align(hdr_len).min(msg_len)..msg_len].to_vec(),
+            });
+
+            rest = rest.get(align(msg_len)..).unwrap_or_default();
+        }
+
+        Ok(responses)
+    }
+
+    // Sends `msg` with the additional `flags` and returns its sequence number.
+    fn send(&self, msg: Message, flags: i32) -> Result<u32> {
+        let seq = self.seq.get().wrapping_add(1);
+        self.seq.set(seq);
+
+        socket::sendto(
+            self.fd.as_raw_fd(),
+            &msg.finish(flags, seq),
+            &socket::NetlinkAddr::new(0, 0),
+            socket::MsgFlags::empty(),
+        )?;
+
+        Ok(seq)
+    }
+}
+
+impl AsRawFd for Netlink {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
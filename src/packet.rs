@@ -0,0 +1,352 @@
+use std::hash::{Hash, Hasher};
+use std::ops;
+use std::sync::{Arc, Mutex};
+
+use crate::device::Device;
+use crate::error::Result;
+
+/// A buffer holding a single packet read from or written to a device.
+///
+/// Used by the batched I/O methods, e.g. [`Device::recv_batch`](crate::Device::recv_batch),
+/// which fill several buffers in one call.
+///
+/// The buffer can reserve room before and after the packet, so headers and trailers can be
+/// added to a received packet in place using [`push_front`](Self::push_front) and
+/// [`push_back`](Self::push_back), e.g. for encapsulating it before forwarding.
+///
+/// Buffers are compared and hashed by the packet they contain.
+#[derive(Debug, Clone)]
+pub struct PacketBuf {
+    buf: Box<[u8]>,
+    // Reserved room before and after the space a packet is read into.
+    reserved_head: usize,
+    reserved_tail: usize,
+    // Start of the packet in `buf`, which moves into the headroom as headers are pushed.
+    start: usize,
+    len: usize,
+    // Whether the packet didn't fit into the buffer when it was read.
+    truncated: bool,
+}
+
+impl PacketBuf {
+    /// Creates an empty buffer which can hold packets of up to `capacity` bytes.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_room(capacity, 0, 0)
+    }
+
+    /// Creates an empty buffer which can hold packets of up to `capacity` bytes, with
+    /// `headroom` bytes reserved before the packet and `tailroom` bytes reserved after it.
+    pub fn with_room(capacity: usize, headroom: usize, tailroom: usize) -> Self {
+        PacketBuf {
+            buf: vec![0; headroom + capacity + tailroom].into_boxed_slice(),
+            reserved_head: headroom,
+            reserved_tail: tailroom,
+            start: headroom,
+            len: 0,
+            truncated: false,
+        }
+    }
+
+    /// Returns the length of the packet in the buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the buffer doesn't contain a packet.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns whether the packet was larger than the capacity of the buffer when it was read,
+    /// in which case only its first [`capacity`](Self::capacity) bytes are in the buffer.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    pub(crate) fn set_truncated(&mut self, truncated: bool) {
+        self.truncated = truncated;
+    }
+
+    /// Returns the maximum length of a packet which can be read into the buffer.
+    pub fn capacity(&self) -> usize {
+        self.buf.len() - self.reserved_head - self.reserved_tail
+    }
+
+    /// Returns the number of bytes which can be added before the packet.
+    pub fn headroom(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the number of bytes which can be added after the packet.
+    pub fn tailroom(&self) -> usize {
+        self.buf.len() - self.start - self.len
+    }
+
+    /// Sets the length of the packet in the buffer, e.g. after reading it into
+    /// [`spare_mut`](Self::spare_mut).
+    ///
+    /// # Panics
+    /// If the packet would extend into the reserved tailroom, which is only extended into
+    /// using [`push_back`](Self::push_back).
+    pub fn set_len(&mut self, len: usize) {
+        assert!(
+            self.start + len <= self.buf.len() - self.reserved_tail,
+            "length exceeds the capacity"
+        );
+
+        self.len = len;
+    }
+
+    /// Shortens the packet to `len` bytes. Does nothing if the packet is already shorter.
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    /// Empties the buffer.
+    pub fn clear(&mut self) {
+        self.start = self.reserved_head;
+        self.len = 0;
+        self.truncated = false;
+    }
+
+    /// Returns the space a packet is read into, which excludes the reserved headroom and
+    /// tailroom, to be filled before calling [`set_len`](Self::set_len).
+    ///
+    /// The buffer must be emptied using [`clear`](Self::clear) first, since the packet in it
+    /// might have been moved into the headroom.
+    pub fn spare_mut(&mut self) -> &mut [u8] {
+        let end = self.buf.len() - self.reserved_tail;
+        &mut self.buf[self.reserved_head..end]
+    }
+
+    /// Extends the packet by `len` bytes at its front and returns them, to be filled with
+    /// a header.
+    ///
+    /// # Panics
+    /// If `len` is larger than the [`headroom`](Self::headroom).
+    pub fn push_front(&mut self, len: usize) -> &mut [u8] {
+        assert!(len <= self.headroom(), "not enough headroom");
+
+        self.start -= len;
+        self.len += len;
+
+        &mut self.buf[self.start..self.start + len]
+    }
+
+    /// Removes `len` bytes from the front of the packet, e.g. to strip a header.
+    ///
+    /// # Panics
+    /// If `len` is larger than the length of the packet.
+    pub fn pull_front(&mut self, len: usize) {
+        assert!(len <= self.len, "length exceeds the packet");
+
+        self.start += len;
+        self.len -= len;
+    }
+
+    /// Extends the packet by `len` bytes at its end and returns them, to be filled with
+    /// a trailer.
+    ///
+    /// # Panics
+    /// If `len` is larger than the [`tailroom`](Self::tailroom).
+    pub fn push_back(&mut self, len: usize) -> &mut [u8] {
+        assert!(len <= self.tailroom(), "not enough tailroom");
+
+        let end = self.start + self.len;
+        self.len += len;
+
+        &mut self.buf[end..end + len]
+    }
+}
+
+impl ops::Deref for PacketBuf {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.buf[self.start..self.start + self.len]
+    }
+}
+
+impl ops::DerefMut for PacketBuf {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.buf[self.start..self.start + self.len]
+    }
+}
+
+impl PartialEq for PacketBuf {
+    fn eq(&self, other: &Self) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl Eq for PacketBuf {}
+
+impl Hash for PacketBuf {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self[..].hash(state);
+    }
+}
+
+impl AsRef<[u8]> for PacketBuf {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl AsMut<[u8]> for PacketBuf {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+impl From<&[u8]> for PacketBuf {
+    fn from(packet: &[u8]) -> Self {
+        PacketBuf {
+            buf: packet.into(),
+            reserved_head: 0,
+            reserved_tail: 0,
+            start: 0,
+            len: packet.len(),
+            truncated: false,
+        }
+    }
+}
+
+/// A pool of [`PacketBuf`]s which are reused instead of being allocated for each packet.
+///
+/// Clones of the pool share the same buffers, so the pool can be used from several threads.
+#[derive(Debug, Clone)]
+pub struct BufferPool(Arc<PoolInner>);
+
+#[derive(Debug)]
+struct PoolInner {
+    capacity: usize,
+    headroom: usize,
+    tailroom: usize,
+    max_idle: usize,
+    free: Mutex<Vec<PacketBuf>>,
+}
+
+// Number of idle buffers kept by a pool created using `BufferPool::new`.
+const DEFAULT_MAX_IDLE: usize = 1024;
+
+impl BufferPool {
+    /// Creates a pool of buffers with the given capacity and reserved room, which keeps up
+    /// to 1024 idle buffers.
+    ///
+    /// See [`PacketBuf::with_room`].
+    pub fn new(capacity: usize, headroom: usize, tailroom: usize) -> Self {
+        Self::with_max_idle(capacity, headroom, tailroom, DEFAULT_MAX_IDLE)
+    }
+
+    /// Creates a pool of buffers with the given capacity and reserved room, which keeps up
+    /// to `max_idle` idle buffers.
+    ///
+    /// Buffers returned to a full pool are freed, so a burst of packets doesn't keep its
+    /// memory allocated after it's processed.
+    pub fn with_max_idle(
+        capacity: usize,
+        headroom: usize,
+        tailroom: usize,
+        max_idle: usize,
+    ) -> Self {
+        BufferPool(Arc::new(PoolInner {
+            capacity,
+            headroom,
+            tailroom,
+            max_idle,
+            free: Mutex::new(Vec::new()),
+        }))
+    }
+
+    /// Creates a pool of buffers which can hold any packet read from `device`.
+    ///
+    /// The capacity of the buffers is computed using [`Device::buffer_size`].
+    pub fn for_device(device: &Device, headroom: usize, tailroom: usize) -> Result<Self> {
+        Ok(Self::new(device.buffer_size()?, headroom, tailroom))
+    }
+
+    /// Returns an empty buffer from the pool, or allocates one if the pool is empty.
+    ///
+    /// The buffer goes back to the pool when it's dropped.
+    pub fn get(&self) -> PooledBuf {
+        let buf = self.0.free.lock().unwrap().pop().unwrap_or_else(|| {
+            PacketBuf::with_room(self.0.capacity, self.0.headroom, self.0.tailroom)
+        });
+
+        PooledBuf {
+            buf: Some(buf),
+            pool: self.0.clone(),
+        }
+    }
+
+    /// Returns the capacity of the buffers in the pool.
+    pub fn buffer_capacity(&self) -> usize {
+        self.0.capacity
+    }
+
+    /// Returns the maximum number of idle buffers kept by the pool.
+    pub fn max_idle(&self) -> usize {
+        self.0.max_idle
+    }
+
+    /// Returns the number of buffers waiting in the pool to be reused.
+    pub fn idle(&self) -> usize {
+        self.0.free.lock().unwrap().len()
+    }
+}
+
+/// A buffer borrowed from a [`BufferPool`], which goes back to the pool when it's dropped.
+#[derive(Debug)]
+pub struct PooledBuf {
+    // Only taken on drop or by `into_inner`.
+    buf: Option<PacketBuf>,
+    pool: Arc<PoolInner>,
+}
+
+impl PooledBuf {
+    /// Takes the buffer out of the pool for good.
+    pub fn into_inner(mut self) -> PacketBuf {
+        self.buf.take().unwrap()
+    }
+}
+
+impl ops::Deref for PooledBuf {
+    type Target = PacketBuf;
+
+    fn deref(&self) -> &Self::Target {
+        self.buf.as_ref().unwrap()
+    }
+}
+
+impl ops::DerefMut for PooledBuf {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.buf.as_mut().unwrap()
+    }
+}
+
+impl AsRef<[u8]> for PooledBuf {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl AsMut<[u8]> for PooledBuf {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+impl Drop for PooledBuf {
+    fn drop(&mut self) {
+        if let Some(mut buf) = self.buf.take() {
+            buf.clear();
+
+            if let Ok(mut free) = self.pool.free.lock() {
+                if free.len() < self.pool.max_idle {
+                    free.push(buf);
+                }
+            }
+        }
+    }
+}
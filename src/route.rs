@@ -0,0 +1,193 @@
+//! Routes whose output interface is a TUN/TAP device.
+
+use std::net;
+
+use crate::bindings;
+use crate::error::{Context, Error, Result};
+use crate::handle::InterfaceHandle;
+use crate::netlink::{self, Message, Netlink};
+
+/// Represents an IPv4/IPv6 route.
+///
+/// Routes are managed through the device they belong to using
+/// [`InterfaceHandle::add_route`], [`InterfaceHandle::del_route`] and [`InterfaceHandle::get_routes`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Route {
+    /// Destination network of the route.
+    pub destination: net::IpAddr,
+
+    /// Prefix length of the destination network.
+    pub prefix_len: u8,
+
+    /// Address of the next hop. `None` means the destination is directly reachable
+    /// through the device.
+    pub gateway: Option<net::IpAddr>,
+
+    /// Priority of the route. Routes with lower metrics are preferred.
+    pub metric: Option<u32>,
+
+    /// Routing table containing the route.
+    pub table: u32,
+
+    /// Source address hint for the packets sent using this route.
+    pub source: Option<net::IpAddr>,
+}
+
+impl Route {
+    /// Creates a route to `destination`/`prefix_len` in the main routing table.
+    pub fn new(destination: impl Into<net::IpAddr>, prefix_len: u8) -> Self {
+        Route {
+            destination: destination.into(),
+            prefix_len,
+            gateway: None,
+            metric: None,
+            table: nix::libc::RT_TABLE_MAIN.into(),
+            source: None,
+        }
+    }
+
+    // Checks that the prefix length fits the destination and all addresses are of the same family.
+    fn validate(&self) -> Result<()> {
+        let max_prefix_len = match self.destination {
+            net::IpAddr::V4(_) => 32,
+            net::IpAddr::V6(_) => 128,
+        };
+
+        if self.prefix_len > max_prefix_len {
+            return Err(Error::InvalidPrefixLength(self.prefix_len));
+        }
+
+        let is_same_family = |addr: &net::IpAddr| addr.is_ipv4() == self.destination.is_ipv4();
+        if !self.gateway.iter().chain(&self.source).all(is_same_family) {
+            return Err(Error::AddressFamilyMismatch);
+        }
+
+        Ok(())
+    }
+
+    // Builds a `RTM_NEWROUTE`/`RTM_DELROUTE` message for this route going through `ifindex`.
+    fn to_message(&self, ty: u16, flags: i32, ifindex: i32) -> Result<Message> {
+        self.validate()?;
+
+        let is_add = ty == nix::libc::RTM_NEWROUTE;
+
+        let rtmsg = bindings::rtmsg {
+            rtm_family: netlink::family(&self.destination),
+            rtm_dst_len: self.prefix_len,
+            rtm_src_len: 0,
+            rtm_tos: 0,
+            // Tables with ids larger than 255 can only be specified using the `RTA_TABLE` attribute.
+            rtm_table: u8::try_from(self.table).unwrap_or(nix::libc::RT_TABLE_UNSPEC),
+            // When deleting, the zero values act as wildcards.
+            rtm_protocol: if is_add { nix::libc::RTPROT_BOOT } else { 0 },
+            rtm_scope: match (is_add, self.gateway) {
+                (false, _) => nix::libc::RT_SCOPE_NOWHERE,
+                (true, None) => nix::libc::RT_SCOPE_LINK,
+                (true, Some(_)) => nix::libc::RT_SCOPE_UNIVERSE,
+            },
+            rtm_type: if is_add { nix::libc::RTN_UNICAST } else { 0 },
+            rtm_flags: 0,
+        };
+
+        let mut msg = Message::new(ty, flags, &rtmsg)
+            .attr(nix::libc::RTA_DST, &netlink::ip_octets(&self.destination))
+            .attr_u32(nix::libc::RTA_OIF, ifindex as u32)
+            .attr_u32(nix::libc::RTA_TABLE, self.table);
+
+        if let Some(gateway) = &self.gateway {
+            msg = msg.attr(nix::libc::RTA_GATEWAY, &netlink::ip_octets(gateway));
+        }
+
+        if let Some(metric) = self.metric {
+            msg = msg.attr_u32(nix::libc::RTA_PRIORITY, metric);
+        }
+
+        if let Some(source) = &self.source {
+            msg = msg.attr(nix::libc::RTA_PREFSRC, &netlink::ip_octets(source));
+        }
+
+        Ok(msg)
+    }
+
+    // Parses a route from a `RTM_NEWROUTE` message and returns it with the index of its output interface.
+    fn from_response(response: &netlink::Response) -> Option<(Self, Option<i32>)> {
+        let rtmsg: bindings::rtmsg = response.header()?;
+
+        // We only care about regular routes. The local, broadcast, and multicast routes are
+        // managed by the kernel, and cloned routes are just entries in the route cache.
+        if rtmsg.rtm_type != nix::libc::RTN_UNICAST
+            || rtmsg.rtm_flags & nix::libc::RTM_F_CLONED != 0
+        {
+            return None;
+        }
+
+        // Routes without a `RTA_DST` attribute are default routes.
+        let destination: net::IpAddr = match rtmsg.rtm_family as i32 {
+            nix::libc::AF_INET => net::Ipv4Addr::UNSPECIFIED.into(),
+            nix::libc::AF_INET6 => net::Ipv6Addr::UNSPECIFIED.into(),
+            _ => return None,
+        };
+
+        let mut route = Route {
+            table: rtmsg.rtm_table.into(),
+            ..Route::new(destination, rtmsg.rtm_dst_len)
+        };
+        let mut ifindex = None;
+
+        for (ty, payload) in response.attrs::<bindings::rtmsg>() {
+            match ty {
+                nix::libc::RTA_DST => route.destination = netlink::to_ip(payload)?,
+                nix::libc::RTA_GATEWAY => route.gateway = netlink::to_ip(payload),
+                nix::libc::RTA_PREFSRC => route.source = netlink::to_ip(payload),
+                nix::libc::RTA_PRIORITY => route.metric = netlink::to_u32(payload),
+                nix::libc::RTA_TABLE => route.table = netlink::to_u32(payload)?,
+                nix::libc::RTA_OIF => ifindex = netlink::to_u32(payload).map(|i| i as i32),
+                _ => {}
+            }
+        }
+
+        Some((route, ifindex))
+    }
+}
+
+impl InterfaceHandle {
+    /// Adds `route` to its routing table with the device as its output interface.
+    pub fn add_route(&self, route: &Route) -> Result<()> {
+        let msg = route.to_message(
+            nix::libc::RTM_NEWROUTE,
+            nix::libc::NLM_F_CREATE | nix::libc::NLM_F_EXCL,
+            self.get_index()?,
+        )?;
+
+        Netlink::new()?
+            .request(msg)
+            .context("RTM_NEWROUTE", &self.name)
+    }
+
+    /// Deletes `route` from its routing table.
+    pub fn del_route(&self, route: &Route) -> Result<()> {
+        let msg = route.to_message(nix::libc::RTM_DELROUTE, 0, self.get_index()?)?;
+
+        Netlink::new()?
+            .request(msg)
+            .context("RTM_DELROUTE", &self.name)
+    }
+
+    /// Returns the IPv4 and IPv6 routes whose output interface is the device.
+    pub fn get_routes(&self) -> Result<Vec<Route>> {
+        let ifindex = self.get_index()?;
+
+        let rtmsg: bindings::rtmsg = unsafe { std::mem::zeroed() };
+        let msg = Message::new(nix::libc::RTM_GETROUTE, 0, &rtmsg);
+
+        Ok(Netlink::new()?
+            .dump(msg)
+            .context("RTM_GETROUTE", &self.name)?
+            .iter()
+            .filter_map(Route::from_response)
+            // We are only interested in the routes going through our interface.
+            .filter(|(_, oif)| *oif == Some(ifindex))
+            .map(|(route, _)| route)
+            .collect())
+    }
+}
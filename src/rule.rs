@@ -0,0 +1,231 @@
+//! Policy routing rules for the traffic of a TUN/TAP device.
+
+use std::collections::HashSet;
+use std::{net, ops};
+
+use crate::bindings;
+use crate::error::{Context, Error, Result};
+use crate::handle::InterfaceHandle;
+use crate::netlink::{self, Message, Netlink};
+
+// Attributes and actions of a rule. Source: `linux/fib_rules.h`.
+const FRA_SRC: u16 = 2;
+const FRA_IIFNAME: u16 = 3;
+const FRA_PRIORITY: u16 = 6;
+const FRA_FWMARK: u16 = 10;
+const FRA_TABLE: u16 = 15;
+const FRA_FWMASK: u16 = 16;
+const FRA_UID_RANGE: u16 = 20;
+const FR_ACT_TO_TBL: u8 = 1;
+
+/// Address family of a rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Family {
+    Ipv4,
+    Ipv6,
+}
+
+impl Family {
+    fn to_raw(self) -> u8 {
+        match self {
+            Family::Ipv4 => nix::libc::AF_INET as u8,
+            Family::Ipv6 => nix::libc::AF_INET6 as u8,
+        }
+    }
+
+    fn from_raw(family: u8) -> Option<Self> {
+        match family as i32 {
+            nix::libc::AF_INET => Some(Family::Ipv4),
+            nix::libc::AF_INET6 => Some(Family::Ipv6),
+            _ => None,
+        }
+    }
+}
+
+/// Represents a policy routing rule which looks up a routing table for the matching packets.
+///
+/// A packet matches the rule if it matches all of the specified selectors. A rule without any
+/// selectors matches all packets of its family.
+///
+/// Rules are managed through the device using [`InterfaceHandle::add_rule`], [`InterfaceHandle::del_rule`]
+/// and [`InterfaceHandle::get_rules`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rule {
+    /// Address family of the packets matched by the rule.
+    pub family: Family,
+
+    /// Priority of the rule. Rules with lower priorities are evaluated first.
+    /// If `None`, the kernel picks one when the rule is added.
+    pub priority: Option<u32>,
+
+    /// Matches the packets with this firewall mark.
+    pub fwmark: Option<u32>,
+
+    /// Mask applied to the firewall mark of the packets before comparing it with `fwmark`.
+    /// `None` means the whole mark is compared.
+    pub fwmask: Option<u32>,
+
+    /// Matches the packets whose source address is inside this network, given as an
+    /// address and a prefix length.
+    pub source: Option<(net::IpAddr, u8)>,
+
+    /// Matches the packets sent by the sockets of the users in this range.
+    pub uid_range: Option<ops::RangeInclusive<u32>>,
+
+    /// Matches the packets coming in from the interface with this name.
+    pub iif: Option<String>,
+
+    /// Routing table to look up for the matching packets.
+    pub table: u32,
+}
+
+impl Rule {
+    /// Creates a rule matching all packets of `family`, which looks up `table`.
+    pub fn new(family: Family, table: u32) -> Self {
+        Rule {
+            family,
+            priority: None,
+            fwmark: None,
+            fwmask: None,
+            source: None,
+            uid_range: None,
+            iif: None,
+            table,
+        }
+    }
+
+    // Builds a `RTM_NEWRULE`/`RTM_DELRULE` message for this rule.
+    fn to_message(&self, ty: u16, flags: i32) -> Result<Message> {
+        let mut fib_rule_hdr: bindings::fib_rule_hdr = unsafe { std::mem::zeroed() };
+        fib_rule_hdr.family = self.family.to_raw();
+        // Tables with ids larger than 255 can only be specified using the `FRA_TABLE` attribute.
+        fib_rule_hdr.table = u8::try_from(self.table).unwrap_or(nix::libc::RT_TABLE_UNSPEC);
+        fib_rule_hdr.action = FR_ACT_TO_TBL;
+
+        if let Some((addr, prefix_len)) = &self.source {
+            if Family::from_raw(netlink::family(addr)) != Some(self.family) {
+                return Err(Error::AddressFamilyMismatch);
+            }
+
+            if *prefix_len > if addr.is_ipv4() { 32 } else { 128 } {
+                return Err(Error::InvalidPrefixLength(*prefix_len));
+            }
+
+            fib_rule_hdr.src_len = *prefix_len;
+        }
+
+        let mut msg = Message::new(ty, flags, &fib_rule_hdr).attr_u32(FRA_TABLE, self.table);
+
+        if let Some(priority) = self.priority {
+            msg = msg.attr_u32(FRA_PRIORITY, priority);
+        }
+
+        if let Some(fwmark) = self.fwmark {
+            msg = msg.attr_u32(FRA_FWMARK, fwmark);
+        }
+
+        if let Some(fwmask) = self.fwmask {
+            msg = msg.attr_u32(FRA_FWMASK, fwmask);
+        }
+
+        if let Some((addr, _)) = &self.source {
+            msg = msg.attr(FRA_SRC, &netlink::ip_octets(addr));
+        }
+
+        if let Some(uid_range) = &self.uid_range {
+            let range = bindings::fib_rule_uid_range {
+                start: *uid_range.start(),
+                end: *uid_range.end(),
+            };
+
+            msg = msg.attr(FRA_UID_RANGE, netlink::as_bytes(&range));
+        }
+
+        if let Some(iif) = &self.iif {
+            msg = msg.attr_str(FRA_IIFNAME, iif);
+        }
+
+        Ok(msg)
+    }
+
+    // Parses a rule from a `RTM_NEWRULE` message.
+    fn from_response(response: &netlink::Response) -> Option<Self> {
+        let fib_rule_hdr: bindings::fib_rule_hdr = response.header()?;
+
+        // We only represent rules which look up a table.
+        if fib_rule_hdr.action != FR_ACT_TO_TBL {
+            return None;
+        }
+
+        let mut rule = Rule::new(
+            Family::from_raw(fib_rule_hdr.family)?,
+            fib_rule_hdr.table.into(),
+        );
+
+        for (ty, payload) in response.attrs::<bindings::fib_rule_hdr>() {
+            match ty {
+                FRA_TABLE => rule.table = netlink::to_u32(payload)?,
+                FRA_PRIORITY => rule.priority = netlink::to_u32(payload),
+                FRA_FWMARK => rule.fwmark = netlink::to_u32(payload),
+                // The kernel reports the default mask which compares the whole mark as well.
+                FRA_FWMASK => rule.fwmask = netlink::to_u32(payload).filter(|m| *m != u32::MAX),
+                FRA_SRC => {
+                    rule.source = netlink::to_ip(payload).map(|ip| (ip, fib_rule_hdr.src_len))
+                }
+                FRA_UID_RANGE => {
+                    let range: bindings::fib_rule_uid_range = netlink::read(payload)?;
+                    rule.uid_range = Some(range.start..=range.end);
+                }
+                FRA_IIFNAME => rule.iif = Some(netlink::to_string(payload)),
+                _ => {}
+            }
+        }
+
+        Some(rule)
+    }
+}
+
+impl InterfaceHandle {
+    /// Adds `rule` to the routing policy database.
+    pub fn add_rule(&self, rule: &Rule) -> Result<()> {
+        let msg = rule.to_message(
+            nix::libc::RTM_NEWRULE,
+            nix::libc::NLM_F_CREATE | nix::libc::NLM_F_EXCL,
+        )?;
+
+        Netlink::new()?
+            .request(msg)
+            .context("RTM_NEWRULE", &self.name)
+    }
+
+    /// Deletes `rule` from the routing policy database.
+    pub fn del_rule(&self, rule: &Rule) -> Result<()> {
+        let msg = rule.to_message(nix::libc::RTM_DELRULE, 0)?;
+
+        Netlink::new()?
+            .request(msg)
+            .context("RTM_DELRULE", &self.name)
+    }
+
+    /// Returns the rules related to the device.
+    ///
+    /// A rule is related to the device if it matches the packets coming in from the device,
+    /// or it looks up a table which contains at least one of the routes of the device.
+    pub fn get_rules(&self) -> Result<Vec<Rule>> {
+        let name = self.current_name()?;
+        let tables: HashSet<u32> = self.get_routes()?.iter().map(|r| r.table).collect();
+
+        let fib_rule_hdr: bindings::fib_rule_hdr = unsafe { std::mem::zeroed() };
+        let msg = Message::new(nix::libc::RTM_GETRULE, 0, &fib_rule_hdr);
+
+        Ok(Netlink::new()?
+            .dump(msg)
+            .context("RTM_GETRULE", &self.name)?
+            .iter()
+            .filter_map(Rule::from_response)
+            .filter(|rule| {
+                tables.contains(&rule.table) || rule.iif.as_deref() == Some(name.as_str())
+            })
+            .collect())
+    }
+}
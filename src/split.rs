@@ -0,0 +1,193 @@
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::{io, ops};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::asyncd::AsyncDevice;
+use crate::error::Result;
+use crate::multiq::QueueSlot;
+use crate::PacketBuf;
+
+/// Borrowed read half of an [`AsyncDevice`], created by [`AsyncDevice::split`].
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[derive(Debug)]
+pub struct ReadHalf<'a>(&'a AsyncDevice);
+
+/// Borrowed write half of an [`AsyncDevice`], created by [`AsyncDevice::split`].
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[derive(Debug)]
+pub struct WriteHalf<'a>(&'a AsyncDevice);
+
+/// Owned read half of an [`AsyncDevice`], created by [`AsyncDevice::into_split`].
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[derive(Debug)]
+pub struct OwnedReadHalf(Arc<SplitDevice>);
+
+/// Owned write half of an [`AsyncDevice`], created by [`AsyncDevice::into_split`].
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[derive(Debug)]
+pub struct OwnedWriteHalf(Arc<SplitDevice>);
+
+// The device shared by the owned halves.
+#[derive(Debug)]
+struct SplitDevice {
+    // Closed before the slot of a queue is dropped, see `MQDevice`.
+    device: AsyncDevice,
+    _slot: Option<Arc<QueueSlot>>,
+}
+
+impl ops::Deref for SplitDevice {
+    type Target = AsyncDevice;
+
+    fn deref(&self) -> &Self::Target {
+        &self.device
+    }
+}
+
+impl AsyncDevice {
+    /// Splits the device into read and write halves which borrow the device.
+    ///
+    /// Unlike `tokio::io::split`, the halves don't need to lock the device, since reading
+    /// and writing a TUN/TAP device are independent of each other.
+    pub fn split(&self) -> (ReadHalf<'_>, WriteHalf<'_>) {
+        (ReadHalf(self), WriteHalf(self))
+    }
+
+    /// Splits the device into owned read and write halves, which can be moved into different tasks.
+    ///
+    /// The device is closed when both halves are dropped.
+    pub fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf) {
+        self.into_split_with(None)
+    }
+
+    // Splits the device, keeping the queue in the registry of its interface until both halves
+    // are dropped.
+    pub(crate) fn into_split_with(
+        self,
+        slot: Option<Arc<QueueSlot>>,
+    ) -> (OwnedReadHalf, OwnedWriteHalf) {
+        let device = Arc::new(SplitDevice {
+            device: self,
+            _slot: slot,
+        });
+
+        (OwnedReadHalf(device.clone()), OwnedWriteHalf(device))
+    }
+}
+
+// Implements the reading side of the device for a read half.
+macro_rules! impl_read_half {
+    ($ty:ty) => {
+        impl $ty {
+            /// Asyncronously reads data from the device and writes to the `buf`.
+            pub async fn recv(&self, buf: &mut [u8]) -> Result<usize> {
+                self.0.recv(buf).await
+            }
+
+            /// Tries to read data from the device and fill the buffer `buf`.
+            pub fn try_recv(&self, buf: &mut [u8]) -> Result<usize> {
+                self.0.try_recv(buf)
+            }
+
+            /// Asyncronously reads a packet from the device into `buf`, after its reserved headroom.
+            pub async fn recv_buf(&self, buf: &mut PacketBuf) -> Result<usize> {
+                self.0.recv_buf(buf).await
+            }
+
+            /// Tries to read a packet from the device into `buf`, after its reserved headroom.
+            pub fn try_recv_buf(&self, buf: &mut PacketBuf) -> Result<usize> {
+                self.0.try_recv_buf(buf)
+            }
+
+            /// Returns the device this half belongs to.
+            pub fn device(&self) -> &AsyncDevice {
+                &self.0
+            }
+        }
+
+        impl AsyncRead for $ty {
+            fn poll_read(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut ReadBuf<'_>,
+            ) -> Poll<io::Result<()>> {
+                self.0.poll_recv(cx, buf)
+            }
+        }
+
+        impl futures::io::AsyncRead for $ty {
+            fn poll_read(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut [u8],
+            ) -> Poll<io::Result<usize>> {
+                self.0.poll_recv_packet(cx, buf)
+            }
+        }
+    };
+}
+
+// Implements the writing side of the device for a write half.
+macro_rules! impl_write_half {
+    ($ty:ty) => {
+        impl $ty {
+            /// Asyncronously writes data from `buf` to the device.
+            pub async fn send(&self, buf: &[u8]) -> Result<usize> {
+                self.0.send(buf).await
+            }
+
+            /// Tries to write data from the buf to the device.
+            pub fn try_send(&self, buf: &[u8]) -> Result<usize> {
+                self.0.try_send(buf)
+            }
+
+            /// Returns the device this half belongs to.
+            pub fn device(&self) -> &AsyncDevice {
+                &self.0
+            }
+        }
+
+        impl AsyncWrite for $ty {
+            fn poll_write(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                self.0.poll_send(cx, buf)
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        impl futures::io::AsyncWrite for $ty {
+            fn poll_write(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                self.0.poll_send(cx, buf)
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+    };
+}
+
+impl_read_half!(ReadHalf<'_>);
+impl_read_half!(OwnedReadHalf);
+impl_write_half!(WriteHalf<'_>);
+impl_write_half!(OwnedWriteHalf);
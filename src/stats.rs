@@ -0,0 +1,120 @@
+//! Statistics of a TUN/TAP device.
+
+use crate::bindings;
+use crate::error::{Context, Error, Result};
+use crate::handle::InterfaceHandle;
+use crate::netlink::{Message, Netlink};
+
+/// Counters of the packets that went through the device.
+///
+/// The counters are named from the point of view of the kernel. So for a TUN/TAP device:
+/// * `tx` counters are about the packets the kernel passed to the user space program,
+///   i.e. the packets read from the device.
+/// * `rx` counters are about the packets the user space program passed to the kernel,
+///   i.e. the packets written to the device.
+///
+/// For more info: `struct rtnl_link_stats64` in `linux/if_link.h`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Stats {
+    /// Number of packets written to the device.
+    pub rx_packets: u64,
+
+    /// Number of packets read from the device.
+    pub tx_packets: u64,
+
+    /// Number of bytes written to the device.
+    pub rx_bytes: u64,
+
+    /// Number of bytes read from the device.
+    pub tx_bytes: u64,
+
+    /// Number of written packets which were malformed.
+    pub rx_errors: u64,
+
+    /// Number of packets which couldn't be passed to the user space program due to an error.
+    pub tx_errors: u64,
+
+    /// Number of written packets which were dropped by the kernel.
+    pub rx_dropped: u64,
+
+    /// Number of packets which were dropped instead of being passed to the user space program.
+    ///
+    /// For a TUN/TAP device, this mostly counts the packets dropped because the queue of the
+    /// device was full (the reader fell behind), or because no queue was attached to the device.
+    ///
+    /// **NOTE**: The kernel doesn't count the reasons separately, so the packets dropped since
+    /// the reader fell behind can't be told apart from the ones dropped by a socket filter, or
+    /// the ones which failed to be prepared for the reader. The reasons are only exposed
+    /// through the `skb:kfree_skb` tracepoint.
+    pub tx_dropped: u64,
+
+    /// Number of multicast packets written to the device.
+    pub multicast: u64,
+
+    /// Number of written packets that were dropped since their length was invalid.
+    pub rx_length_errors: u64,
+
+    /// Number of written packets that were dropped since they were malformed, e.g. their
+    /// virtio-net header was invalid.
+    pub rx_frame_errors: u64,
+
+    /// Number of written packets that were dropped due to a FIFO overrun.
+    pub rx_fifo_errors: u64,
+
+    /// Number of packets that were dropped due to a FIFO overrun before reaching the reader.
+    pub tx_fifo_errors: u64,
+
+    /// Number of written packets that were dropped since no protocol handler was registered
+    /// for them.
+    pub rx_nohandler: u64,
+}
+
+impl Stats {
+    // Parses the payload of an `IFLA_STATS64` attribute.
+    //
+    // The struct has grown over time, so we read as many counters as the kernel gave us.
+    fn from_stats64(payload: &[u8]) -> Self {
+        let counters: Vec<u64> = payload
+            .chunks_exact(8)
+            .map(|chunk| u64::from_ne_bytes(chunk.try_into().unwrap()))
+            .collect();
+
+        let counter = |index: usize| counters.get(index).copied().unwrap_or_default();
+
+        Stats {
+            rx_packets: counter(0),
+            tx_packets: counter(1),
+            rx_bytes: counter(2),
+            tx_bytes: counter(3),
+            rx_errors: counter(4),
+            tx_errors: counter(5),
+            rx_dropped: counter(6),
+            tx_dropped: counter(7),
+            multicast: counter(8),
+            rx_length_errors: counter(10),
+            rx_frame_errors: counter(13),
+            rx_fifo_errors: counter(14),
+            tx_fifo_errors: counter(18),
+            rx_nohandler: counter(23),
+        }
+    }
+}
+
+impl InterfaceHandle {
+    /// Returns the statistics of the device.
+    pub fn stats(&self) -> Result<Stats> {
+        let mut ifinfomsg: bindings::ifinfomsg = unsafe { std::mem::zeroed() };
+        ifinfomsg.ifi_index = self.get_index()?;
+
+        let msg = Message::new(nix::libc::RTM_GETLINK, 0, &ifinfomsg);
+        let response = Netlink::new()?
+            .get(msg)
+            .context("RTM_GETLINK", &self.name)?;
+
+        response
+            .attrs::<bindings::ifinfomsg>()
+            .find(|(ty, _)| *ty == nix::libc::IFLA_STATS64)
+            .map(|(_, payload)| Stats::from_stats64(payload))
+            .ok_or(Error::MalformedNetlinkMessage)
+    }
+}
@@ -0,0 +1,155 @@
+//! Packet steering of the queues of a multiqueue TUN/TAP device.
+//!
+//! These are the knobs under `/sys/class/net/<name>/queues/`. Receive packet steering (RPS)
+//! picks the CPU processing the packets written to a queue, so it can be set to agree with the
+//! CPU the worker of the queue is pinned to, and receive flow steering (RFS) tracks the flows
+//! of a queue for steering them to the CPUs of the applications consuming them.
+//!
+//! Transmit packet steering (XPS) doesn't apply to TUN/TAP devices, since they pick the queue
+//! of the packets the kernel sends to them by themselves: by the hash of the flow, preferring
+//! the queue the packets of the flow were last written to, or by an eBPF steering program. So
+//! the XPS setting is only exposed for completeness, and only RPS and RFS steer the packets of
+//! a queue.
+//!
+//! For more info: `Documentation/networking/scaling.rst` in the kernel source tree.
+
+use std::fs;
+
+use crate::error::{Context, Error, Result};
+use crate::multiq::MQDevice;
+use crate::InterfaceHandle;
+
+impl MQDevice {
+    /// Sets the `xps_cpus` of the transmit queue with the index of this queue.
+    ///
+    /// **NOTE**: TUN/TAP devices ignore XPS when picking the queue of a packet sent to them,
+    /// so this doesn't steer any packets to this queue. The queue of a flow follows the queue
+    /// its packets are written to instead.
+    ///
+    /// The setting belongs to the index of the queue in the kernel, so it's written to the new
+    /// index of the queue when the queues are reindexed, see [`queue_index`](Self::queue_index).
+    pub fn set_xps_cpus(&self, cpus: &[usize]) -> Result<()> {
+        self.set("tx", "xps_cpus", to_cpu_mask(cpus))
+    }
+
+    /// Returns the `xps_cpus` of the transmit queue with the index of this queue.
+    pub fn get_xps_cpus(&self) -> Result<Vec<usize>> {
+        let (path, value) = self.get("tx", "xps_cpus")?;
+
+        from_cpu_mask(&value).ok_or(Error::UnexpectedSysfsValue(path, value))
+    }
+
+    /// Sets the CPUs which process the packets written to this queue.
+    ///
+    /// The setting belongs to the index of the queue in the kernel, so it's written to the new
+    /// index of the queue when the queues are reindexed, see [`queue_index`](Self::queue_index).
+    pub fn set_rps_cpus(&self, cpus: &[usize]) -> Result<()> {
+        self.set("rx", "rps_cpus", to_cpu_mask(cpus))
+    }
+
+    /// Returns the CPUs which process the packets written to this queue.
+    pub fn get_rps_cpus(&self) -> Result<Vec<usize>> {
+        let (path, value) = self.get("rx", "rps_cpus")?;
+
+        from_cpu_mask(&value).ok_or(Error::UnexpectedSysfsValue(path, value))
+    }
+
+    /// Sets the number of flows of this queue tracked by receive flow steering (RFS).
+    ///
+    /// Like [`set_rps_cpus`](Self::set_rps_cpus), the setting follows the queue when the
+    /// queues are reindexed.
+    pub fn set_rps_flow_cnt(&self, count: u32) -> Result<()> {
+        self.set("rx", "rps_flow_cnt", count.to_string())
+    }
+
+    /// Returns the number of flows of this queue tracked by receive flow steering (RFS).
+    pub fn get_rps_flow_cnt(&self) -> Result<u32> {
+        let (path, value) = self.get("rx", "rps_flow_cnt")?;
+
+        value
+            .trim()
+            .parse()
+            .map_err(|_| Error::UnexpectedSysfsValue(path, value))
+    }
+
+    fn set(&self, dir: &'static str, name: &'static str, value: String) -> Result<()> {
+        let slot = self.slot();
+
+        slot.lock().set(slot.id(), dir, name, value)
+    }
+
+    fn get(&self, dir: &str, name: &str) -> Result<(String, String)> {
+        let slot = self.slot();
+
+        slot.lock().get(slot.id(), dir, name)
+    }
+}
+
+// Returns the path of the sysfs file called `name` of the queue at `index` in the
+// `dir`(`tx`/`rx`) direction.
+fn sysfs_path(handle: &InterfaceHandle, index: usize, dir: &str, name: &str) -> Result<String> {
+    Ok(format!(
+        "/sys/class/net/{}/queues/{}-{}/{}",
+        handle.current_name()?,
+        dir,
+        index,
+        name
+    ))
+}
+
+// Reads the sysfs file, returning its path along with its contents.
+pub(crate) fn read_sysfs(
+    handle: &InterfaceHandle,
+    index: usize,
+    dir: &str,
+    name: &str,
+) -> Result<(String, String)> {
+    let path = sysfs_path(handle, index, dir, name)?;
+    let value = fs::read_to_string(&path).context("read", handle.name())?;
+
+    Ok((path, value))
+}
+
+pub(crate) fn write_sysfs(
+    handle: &InterfaceHandle,
+    index: usize,
+    dir: &str,
+    name: &str,
+    value: &str,
+) -> Result<()> {
+    fs::write(sysfs_path(handle, index, dir, name)?, value).context("write", handle.name())
+}
+
+// Formats `cpus` as a hex CPU mask, in comma separated groups of 32 bits.
+fn to_cpu_mask(cpus: &[usize]) -> String {
+    let group_count = cpus.iter().max().map_or(1, |max| max / 32 + 1);
+    let mut groups = vec![0u32; group_count];
+
+    for cpu in cpus {
+        groups[group_count - 1 - cpu / 32] |= 1 << (cpu % 32);
+    }
+
+    groups
+        .iter()
+        .map(|group| format!("{:08x}", group))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// Parses a hex CPU mask formatted like `to_cpu_mask`.
+fn from_cpu_mask(mask: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+
+    let digits = mask.trim().chars().filter(|c| *c != ',');
+    for (position, digit) in digits.rev().enumerate() {
+        let digit = digit.to_digit(16)?;
+
+        cpus.extend(
+            (0..4)
+                .filter(|bit| digit & (1 << bit) != 0)
+                .map(|bit| position * 4 + bit),
+        );
+    }
+
+    Some(cpus)
+}
@@ -0,0 +1,200 @@
+//! `Stream` and `Sink` adapters for async devices.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::{Sink, Stream};
+use nix::errno::Errno;
+
+use crate::asyncd::{AsyncTap, AsyncTun};
+use crate::error::{Error, Result};
+use crate::split::{OwnedReadHalf, OwnedWriteHalf, ReadHalf, WriteHalf};
+use crate::{BufferPool, PooledBuf};
+
+/// Adapts an async device, or one of its halves, into a [`Stream`] of the packets read from
+/// it and a [`Sink`] of the packets written to it.
+///
+/// Each packet is read into a buffer taken from a [`BufferPool`], which goes back to the pool
+/// once the packet is dropped. A packet which doesn't fit fails with
+/// [`Error::PacketTruncated`](crate::error::Error::PacketTruncated) without ending the stream,
+/// while any other error ends the stream after it's returned.
+///
+/// The sink writes a packet as soon as it's sent, and only holds on to a copy of it while
+/// the device is not ready to be written to.
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[derive(Debug)]
+pub struct Packets<T> {
+    io: T,
+    pool: BufferPool,
+    // Buffer which is kept while waiting for the device to become readable.
+    read_buf: Option<PooledBuf>,
+    // Set once reading failed with an error which can't be recovered from.
+    done: bool,
+    // Packet which is waiting for the device to become writable.
+    pending: Option<Vec<u8>>,
+}
+
+impl<T> Packets<T> {
+    /// Creates an adapter which reads the packets into buffers of `buffer_size` bytes.
+    ///
+    /// The devices and their halves also have an `into_packets` method, which sizes the
+    /// buffers from the MTU of the device.
+    pub fn with_buffer_size(io: T, buffer_size: usize) -> Self {
+        Self::with_pool(io, BufferPool::new(buffer_size, 0, 0))
+    }
+
+    /// Creates an adapter which reads the packets into buffers taken from `pool`, e.g. for
+    /// reserving room for headers, or sharing the buffers with other devices.
+    pub fn with_pool(io: T, pool: BufferPool) -> Self {
+        Packets {
+            io,
+            pool,
+            read_buf: None,
+            done: false,
+            pending: None,
+        }
+    }
+
+    /// Returns the size of the buffers the packets are read into.
+    pub fn buffer_size(&self) -> usize {
+        self.pool.buffer_capacity()
+    }
+
+    /// Sets the size of the buffers the next packets are read into.
+    ///
+    /// The buffers are taken from a new pool from now on.
+    pub fn set_buffer_size(&mut self, buffer_size: usize) {
+        self.set_pool(BufferPool::new(buffer_size, 0, 0));
+    }
+
+    /// Returns the pool the buffers of the packets are taken from.
+    pub fn pool(&self) -> &BufferPool {
+        &self.pool
+    }
+
+    /// Sets the pool the buffers of the next packets are taken from.
+    pub fn set_pool(&mut self, pool: BufferPool) {
+        self.pool = pool;
+        self.read_buf = None;
+    }
+
+    /// Returns the adapted device or half.
+    pub fn get_ref(&self) -> &T {
+        &self.io
+    }
+
+    /// Returns the adapted device or half, dropping a packet waiting to be written, if any.
+    pub fn into_inner(self) -> T {
+        self.io
+    }
+}
+
+// Implements the conversion of `$ty` into `Packets`, where `$device` gets the `AsyncDevice`
+// out of `$io`.
+macro_rules! impl_into_packets {
+    ($ty:ty, $($lt:lifetime,)? |$io:ident| $device:expr) => {
+        impl$(<$lt>)? $ty {
+            /// Adapts into a [`Stream`]/[`Sink`] of packets, whose buffers can hold any packet
+            /// read from the device, see [`Device::buffer_size`](crate::Device::buffer_size).
+            pub fn into_packets(self) -> Result<Packets<Self>> {
+                let $io = &self;
+                let buffer_size = $device.buffer_size()?;
+
+                Ok(Packets::with_buffer_size(self, buffer_size))
+            }
+        }
+    };
+}
+
+// Implements the `Stream` of the packets read from a device for `Packets<$ty>`, where
+// `$device` gets the `AsyncDevice` out of `$io`.
+macro_rules! impl_stream {
+    ($ty:ty, $($lt:lifetime,)? |$io:ident| $device:expr) => {
+        impl$(<$lt>)? Stream for Packets<$ty> {
+            type Item = Result<PooledBuf>;
+
+            fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+                let this = self.get_mut();
+                if this.done {
+                    return Poll::Ready(None);
+                }
+
+                let $io = &this.io;
+                let pool = &this.pool;
+                let buf = this.read_buf.get_or_insert_with(|| pool.get());
+
+                match futures::ready!($device.poll_recv_buf(cx, buf)) {
+                    Ok(()) => Poll::Ready(this.read_buf.take().map(Ok)),
+                    // The buffer is reused for the next packet.
+                    Err(err @ Error::PacketTruncated(_)) => Poll::Ready(Some(Err(err))),
+                    Err(err) => {
+                        this.done = true;
+                        this.read_buf = None;
+
+                        Poll::Ready(Some(Err(err)))
+                    }
+                }
+            }
+        }
+    };
+}
+
+// Implements the `Sink` of the packets written to a device for `Packets<$ty>`, where
+// `$device` gets the `AsyncDevice` out of `$io`.
+macro_rules! impl_sink {
+    ($ty:ty, $($lt:lifetime,)? |$io:ident| $device:expr) => {
+        impl<$($lt,)? P: AsRef<[u8]>> Sink<P> for Packets<$ty> {
+            type Error = crate::error::Error;
+
+            fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+                <Self as Sink<P>>::poll_flush(self, cx)
+            }
+
+            fn start_send(self: Pin<&mut Self>, packet: P) -> Result<()> {
+                let this = self.get_mut();
+                let $io = &this.io;
+
+                match $device.try_send(packet.as_ref()) {
+                    Err(err) if err.errno() == Some(Errno::EAGAIN) => {
+                        this.pending = Some(packet.as_ref().to_vec());
+                        Ok(())
+                    }
+                    result => result.map(|_| ()),
+                }
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+                let this = self.get_mut();
+                let $io = &this.io;
+
+                if let Some(packet) = &this.pending {
+                    futures::ready!($device.poll_send(cx, packet))?;
+                    this.pending = None;
+                }
+
+                Poll::Ready(Ok(()))
+            }
+
+            fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+                <Self as Sink<P>>::poll_flush(self, cx)
+            }
+        }
+    };
+}
+
+impl_into_packets!(AsyncTun, |io| io);
+impl_into_packets!(AsyncTap, |io| io);
+impl_into_packets!(ReadHalf<'a>, 'a, |io| io.device());
+impl_into_packets!(OwnedReadHalf, |io| io.device());
+impl_into_packets!(WriteHalf<'a>, 'a, |io| io.device());
+impl_into_packets!(OwnedWriteHalf, |io| io.device());
+
+impl_stream!(AsyncTun, |io| io);
+impl_stream!(AsyncTap, |io| io);
+impl_stream!(ReadHalf<'a>, 'a, |io| io.device());
+impl_stream!(OwnedReadHalf, |io| io.device());
+
+impl_sink!(AsyncTun, |io| io);
+impl_sink!(AsyncTap, |io| io);
+impl_sink!(WriteHalf<'a>, 'a, |io| io.device());
+impl_sink!(OwnedWriteHalf, |io| io.device());
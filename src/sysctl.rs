@@ -0,0 +1,235 @@
+//! Per-interface sysctls of a TUN/TAP device.
+//!
+//! These are the knobs under `/proc/sys/net/ipv4/conf/<name>/` and `/proc/sys/net/ipv6/conf/<name>/`.
+//!
+//! For more info: `Documentation/networking/ip-sysctl.rst` in the kernel source tree.
+
+use std::fs;
+
+use crate::error::{Context, Error, Result};
+use crate::handle::InterfaceHandle;
+
+/// Reverse path filtering mode of the interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RpFilter {
+    /// No source validation.
+    Disabled,
+
+    /// Drops the packets whose source is not reachable through the interface they came in from.
+    Strict,
+
+    /// Drops the packets whose source is not reachable through any interface.
+    Loose,
+}
+
+/// Whether the interface accepts IPv6 router advertisements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AcceptRa {
+    /// Router advertisements are ignored.
+    Disabled,
+
+    /// Router advertisements are accepted if forwarding is disabled.
+    Enabled,
+
+    /// Router advertisements are accepted even if forwarding is enabled.
+    Always,
+}
+
+/// How the IPv6 link-local address of the interface is generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddrGenMode {
+    /// Generated from the MAC address of the interface.
+    Eui64,
+
+    /// No link-local address is generated.
+    None,
+
+    /// Generated using the stable privacy secret of the interface.
+    StablePrivacy,
+
+    /// Generated using a random secret.
+    Random,
+}
+
+/// Whether the interface performs IPv6 duplicate address detection (DAD).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AcceptDad {
+    /// DAD is disabled.
+    Disabled,
+
+    /// DAD is enabled.
+    Enabled,
+
+    /// DAD is enabled, and IPv6 is disabled on the interface if a duplicate of the
+    /// MAC based link-local address is found.
+    DisableIpv6OnFailure,
+}
+
+// Conversion between the typed values and the integers stored in the sysctl files.
+trait Value: Sized {
+    fn to_raw(self) -> i32;
+    fn from_raw(value: i32) -> Option<Self>;
+}
+
+impl Value for bool {
+    fn to_raw(self) -> i32 {
+        self.into()
+    }
+
+    fn from_raw(value: i32) -> Option<Self> {
+        Some(value != 0)
+    }
+}
+
+// Implements `Value` for an enum whose variants map to consecutive integers starting from zero.
+macro_rules! impl_value {
+    ($ty:ty, [$($variant:path),+]) => {
+        impl Value for $ty {
+            fn to_raw(self) -> i32 {
+                [$($variant),+].iter().position(|v| *v == self).unwrap() as i32
+            }
+
+            fn from_raw(value: i32) -> Option<Self> {
+                [$($variant),+].get(usize::try_from(value).ok()?).copied()
+            }
+        }
+    };
+}
+
+impl_value!(
+    RpFilter,
+    [RpFilter::Disabled, RpFilter::Strict, RpFilter::Loose]
+);
+impl_value!(
+    AcceptRa,
+    [AcceptRa::Disabled, AcceptRa::Enabled, AcceptRa::Always]
+);
+impl_value!(
+    AddrGenMode,
+    [
+        AddrGenMode::Eui64,
+        AddrGenMode::None,
+        AddrGenMode::StablePrivacy,
+        AddrGenMode::Random
+    ]
+);
+impl_value!(
+    AcceptDad,
+    [
+        AcceptDad::Disabled,
+        AcceptDad::Enabled,
+        AcceptDad::DisableIpv6OnFailure
+    ]
+);
+
+impl InterfaceHandle {
+    /// Disables (`true`) or enables (`false`) IPv6 on the device.
+    pub fn set_disable_ipv6(&self, disable: bool) -> Result<()> {
+        self.write_sysctl("ipv6", "disable_ipv6", disable)
+    }
+
+    /// Returns whether IPv6 is disabled on the device.
+    pub fn get_disable_ipv6(&self) -> Result<bool> {
+        self.read_sysctl("ipv6", "disable_ipv6")
+    }
+
+    /// Enables or disables IPv4 forwarding on the device.
+    pub fn set_ipv4_forwarding(&self, forwarding: bool) -> Result<()> {
+        self.write_sysctl("ipv4", "forwarding", forwarding)
+    }
+
+    /// Returns whether IPv4 forwarding is enabled on the device.
+    pub fn get_ipv4_forwarding(&self) -> Result<bool> {
+        self.read_sysctl("ipv4", "forwarding")
+    }
+
+    /// Enables or disables IPv6 forwarding on the device.
+    pub fn set_ipv6_forwarding(&self, forwarding: bool) -> Result<()> {
+        self.write_sysctl("ipv6", "forwarding", forwarding)
+    }
+
+    /// Returns whether IPv6 forwarding is enabled on the device.
+    pub fn get_ipv6_forwarding(&self) -> Result<bool> {
+        self.read_sysctl("ipv6", "forwarding")
+    }
+
+    /// Sets whether the device accepts IPv6 router advertisements.
+    pub fn set_accept_ra(&self, accept_ra: AcceptRa) -> Result<()> {
+        self.write_sysctl("ipv6", "accept_ra", accept_ra)
+    }
+
+    /// Returns whether the device accepts IPv6 router advertisements.
+    pub fn get_accept_ra(&self) -> Result<AcceptRa> {
+        self.read_sysctl("ipv6", "accept_ra")
+    }
+
+    /// Sets the reverse path filtering mode of the device.
+    ///
+    /// **NOTE**: The kernel uses the maximum of this value and the value of `all` interfaces.
+    pub fn set_rp_filter(&self, rp_filter: RpFilter) -> Result<()> {
+        self.write_sysctl("ipv4", "rp_filter", rp_filter)
+    }
+
+    /// Returns the reverse path filtering mode of the device.
+    pub fn get_rp_filter(&self) -> Result<RpFilter> {
+        self.read_sysctl("ipv4", "rp_filter")
+    }
+
+    /// Enables or disables proxy ARP on the device.
+    pub fn set_proxy_arp(&self, proxy_arp: bool) -> Result<()> {
+        self.write_sysctl("ipv4", "proxy_arp", proxy_arp)
+    }
+
+    /// Returns whether proxy ARP is enabled on the device.
+    pub fn get_proxy_arp(&self) -> Result<bool> {
+        self.read_sysctl("ipv4", "proxy_arp")
+    }
+
+    /// Sets how the IPv6 link-local address of the device is generated.
+    pub fn set_addr_gen_mode(&self, mode: AddrGenMode) -> Result<()> {
+        self.write_sysctl("ipv6", "addr_gen_mode", mode)
+    }
+
+    /// Returns how the IPv6 link-local address of the device is generated.
+    pub fn get_addr_gen_mode(&self) -> Result<AddrGenMode> {
+        self.read_sysctl("ipv6", "addr_gen_mode")
+    }
+
+    /// Sets whether the device performs IPv6 duplicate address detection.
+    pub fn set_accept_dad(&self, accept_dad: AcceptDad) -> Result<()> {
+        self.write_sysctl("ipv6", "accept_dad", accept_dad)
+    }
+
+    /// Returns whether the device performs IPv6 duplicate address detection.
+    pub fn get_accept_dad(&self) -> Result<AcceptDad> {
+        self.read_sysctl("ipv6", "accept_dad")
+    }
+
+    // Returns the path of the sysctl called `name` of the device under the `family` directory.
+    fn sysctl_path(&self, family: &str, name: &str) -> Result<String> {
+        Ok(format!(
+            "/proc/sys/net/{}/conf/{}/{}",
+            family,
+            self.current_name()?,
+            name
+        ))
+    }
+
+    fn read_sysctl<T: Value>(&self, family: &str, name: &str) -> Result<T> {
+        let path = self.sysctl_path(family, name)?;
+        let value = fs::read_to_string(&path).path_context("read", &path)?;
+
+        value
+            .trim()
+            .parse()
+            .ok()
+            .and_then(T::from_raw)
+            .ok_or(Error::UnexpectedSysctlValue(path, value))
+    }
+
+    fn write_sysctl<T: Value>(&self, family: &str, name: &str, value: T) -> Result<()> {
+        let path = self.sysctl_path(family, name)?;
+
+        fs::write(&path, value.to_raw().to_string()).path_context("write", &path)
+    }
+}
@@ -0,0 +1,505 @@
+//! Batched packet I/O using io_uring.
+//!
+//! Reading and writing a device costs a system call per packet, which dominates the CPU
+//! usage at high packet rates. [`IoUring`] keeps a read request in flight for each of its
+//! registered buffers, so the kernel reads the packets in the background and a whole batch
+//! of them is collected with at most one system call. Writes are batched the same way.
+//!
+//! **NOTE**: Requires Linux 5.5 or newer.
+//!
+//! The buffers are registered with the ring, which pins their memory. Before Linux 5.12, the
+//! pinned memory is charged against `RLIMIT_MEMLOCK`, which is only 64 KiB by default on some
+//! distributions, so creating a ring with many or large buffers fails with `ENOMEM` unless the
+//! limit is raised. A single buffer can't be larger than 1 GiB either.
+
+use std::os::unix::prelude::*;
+use std::{fmt, io, mem, ptr, slice};
+
+use io_uring::{cqueue, opcode, squeue, types};
+use nix::errno::Errno;
+
+use crate::device::Device;
+use crate::error::{Context, Result};
+
+// Tags the `user_data` of the requests. The rest of it is the index of the buffer.
+const WRITE_TAG: u64 = 1 << 63;
+const CANCEL_TAG: u64 = 1 << 62;
+
+// The ring of the `io-uring` crate, which doesn't implement `Debug`.
+struct Ring(io_uring::IoUring);
+
+impl fmt::Debug for Ring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Ring").field(&self.0.as_raw_fd()).finish()
+    }
+}
+
+impl Ring {
+    fn new(entries: u32) -> Result<Self> {
+        Ok(Ring(
+            io_uring::IoUring::new(entries).op_context("io_uring_setup")?,
+        ))
+    }
+
+    // Queues `entry` for submission, submitting the queued requests first if the submission
+    // queue is full.
+    //
+    // Safety: The buffer of `entry` must stay valid until the request is completed.
+    unsafe fn push(&mut self, entry: &squeue::Entry) -> Result<()> {
+        if self.0.submission().push(entry).is_err() {
+            self.enter(0)?;
+
+            self.0
+                .submission()
+                .push(entry)
+                .map_err(|_| Errno::EBUSY)
+                .op_context("io_uring_enter")?;
+        }
+
+        Ok(())
+    }
+
+    // Submits the queued requests and waits for at least `min_complete` completions.
+    fn enter(&mut self, min_complete: usize) -> Result<()> {
+        if min_complete == 0 && self.0.submission().is_empty() {
+            return Ok(());
+        }
+
+        loop {
+            match self.0.submit_and_wait(min_complete) {
+                Ok(_) => return Ok(()),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err).op_context("io_uring_enter"),
+            }
+        }
+    }
+
+    // Returns the next completion, if any.
+    fn pop(&mut self) -> Option<cqueue::Entry> {
+        self.0.completion().next()
+    }
+}
+
+/// Reads and writes the packets of a device in batches using io_uring.
+///
+/// The ring has `entries` registered buffers for reading and as many for writing. A read
+/// request is always in flight for each of the read buffers which is not handed out by
+/// [`recv_batch`](Self::recv_batch).
+///
+/// The read requests are cancelled when the ring is dropped. If that fails, the buffers are
+/// leaked, since the kernel might still write into them.
+#[derive(Debug)]
+pub struct IoUring {
+    ring: Ring,
+    device: Device,
+
+    // Registered with the ring. The first half is used for reading, the second for writing.
+    // Only accessed through raw pointers, since the kernel writes into the read buffers in
+    // the background.
+    buffers: Vec<u8>,
+    buf_size: usize,
+    entries: usize,
+
+    // Read completions which are not returned yet, as (buffer index, result).
+    completed: Vec<(usize, i32)>,
+    // Read buffers which are waiting for a new read request.
+    idle: Vec<usize>,
+    reads_in_flight: usize,
+
+    writes_in_flight: usize,
+    written: usize,
+    write_error: Option<Errno>,
+}
+
+impl IoUring {
+    /// Creates an io_uring instance for reading and writing the packets of `device`.
+    ///
+    /// # Arguments
+    /// * `device`: A blocking device, e.g. a [`Tun`](crate::Tun) or a queue of an
+    ///   [`MQTun`](crate::MQTun). Its file descriptor is duplicated, so the device can still
+    ///   be used, though the packets read by the ring won't be read by the device.
+    /// * `entries`: Maximum number of packets read or written in a batch. The requests refer
+    ///   to the `2 * entries` buffers by a 16-bit index, so it can be at most 32768.
+    /// * `buf_size`: Size of each buffer. Longer packets are truncated when read.
+    pub fn new(device: &Device, entries: u32, buf_size: usize) -> Result<Self> {
+        if entries as usize * 2 > usize::from(u16::MAX) + 1 {
+            return Err(Errno::EINVAL).op_context("io_uring_setup");
+        }
+
+        let ring = Ring::new(entries * 2)?;
+        let entries = entries as usize;
+
+        let mut uring = IoUring {
+            ring,
+            device: device.try_clone()?,
+            buffers: vec![0; 2 * entries * buf_size],
+            buf_size,
+            entries,
+            completed: Vec::with_capacity(entries),
+            idle: (0..entries).rev().collect(),
+            reads_in_flight: 0,
+            writes_in_flight: 0,
+            written: 0,
+            write_error: None,
+        };
+
+        let iovecs: Vec<nix::libc::iovec> = (0..2 * entries)
+            .map(|index| nix::libc::iovec {
+                iov_base: uring.buffer(index) as *mut _,
+                iov_len: buf_size,
+            })
+            .collect();
+        unsafe { uring.ring.0.submitter().register_buffers(&iovecs) }
+            .op_context("io_uring_register")?;
+
+        Ok(uring)
+    }
+
+    /// Returns the device whose packets are read and written.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Blocks until at least one packet is read from the device, then returns the packets
+    /// read so far, in order.
+    ///
+    /// The buffers of the returned packets are read into again by the next call.
+    pub fn recv_batch(&mut self) -> Result<Packets<'_>> {
+        self.arm_reads()?;
+
+        let min_complete = if self.completed.is_empty() { 1 } else { 0 };
+        self.ring.enter(min_complete)?;
+        self.reap();
+
+        self.take_packets()
+    }
+
+    /// Writes `packets` to the device, in order.
+    ///
+    /// # Returns
+    /// * `Ok`: Containing the number of written packets. If a packet couldn't be written,
+    ///   the ones after it are not written either.
+    /// * `Err`: If the first packet couldn't be written, or a packet is longer than the buffers.
+    pub fn send_batch<P: AsRef<[u8]>>(&mut self, packets: &[P]) -> Result<usize> {
+        let mut sent = 0;
+
+        for chunk in packets.chunks(self.entries.max(1)) {
+            self.wait_writes()?;
+            self.push_writes(chunk)?;
+            self.wait_writes()?;
+
+            if !self.finish_writes(chunk.len(), &mut sent)? {
+                break;
+            }
+        }
+
+        Ok(sent)
+    }
+
+    // Submits the queued requests and blocks until all writes are completed.
+    fn wait_writes(&mut self) -> Result<()> {
+        self.ring.enter(0)?;
+        self.reap();
+
+        while self.writes_in_flight > 0 {
+            self.ring.enter(1)?;
+            self.reap();
+        }
+
+        Ok(())
+    }
+
+    // Queues a read request for each of the idle read buffers.
+    fn arm_reads(&mut self) -> Result<()> {
+        while let Some(&index) = self.idle.last() {
+            let entry = opcode::ReadFixed::new(
+                types::Fd(self.device.as_raw_fd()),
+                self.buffer(index),
+                self.buf_size as u32,
+                index as u16,
+            )
+            // The device is not seekable, so the current position is used.
+            .offset(u64::MAX)
+            .build()
+            .user_data(index as u64);
+
+            unsafe { self.ring.push(&entry) }?;
+            self.idle.pop();
+            self.reads_in_flight += 1;
+        }
+
+        Ok(())
+    }
+
+    // Copies `chunk` into the write buffers and queues a chain of write requests for it.
+    fn push_writes<P: AsRef<[u8]>>(&mut self, chunk: &[P]) -> Result<()> {
+        if chunk
+            .iter()
+            .any(|packet| packet.as_ref().len() > self.buf_size)
+        {
+            return Err(Errno::EMSGSIZE).context("IORING_OP_WRITE_FIXED", &self.device.name);
+        }
+
+        self.written = 0;
+        self.write_error = None;
+
+        for (i, packet) in chunk.iter().enumerate() {
+            let packet = packet.as_ref();
+            let index = self.entries + i;
+            let buffer = self.buffer(index);
+
+            unsafe { ptr::copy_nonoverlapping(packet.as_ptr(), buffer, packet.len()) };
+
+            let entry = opcode::WriteFixed::new(
+                types::Fd(self.device.as_raw_fd()),
+                buffer,
+                packet.len() as u32,
+                index as u16,
+            )
+            .offset(u64::MAX)
+            .build()
+            .user_data(WRITE_TAG | index as u64);
+
+            // Linking keeps the packets in order and stops at the first failure.
+            let entry = if i + 1 < chunk.len() {
+                entry.flags(squeue::Flags::IO_LINK)
+            } else {
+                entry
+            };
+
+            unsafe { self.ring.push(&entry) }?;
+            self.writes_in_flight += 1;
+        }
+
+        Ok(())
+    }
+
+    // Adds the written packets of the last chunk to `sent`. Returns whether the whole chunk
+    // was written.
+    fn finish_writes(&mut self, len: usize, sent: &mut usize) -> Result<bool> {
+        *sent += self.written;
+
+        match self.write_error.take() {
+            Some(errno) if *sent == 0 => {
+                Err(errno).context("IORING_OP_WRITE_FIXED", &self.device.name)
+            }
+            _ => Ok(self.written == len),
+        }
+    }
+
+    // Collects the completions of the requests.
+    fn reap(&mut self) {
+        while let Some(cqe) = self.ring.pop() {
+            if cqe.user_data() & CANCEL_TAG != 0 {
+                continue;
+            }
+
+            if cqe.user_data() & WRITE_TAG != 0 {
+                self.writes_in_flight -= 1;
+
+                match cqe.result() {
+                    res if res >= 0 => self.written += 1,
+                    // The writes linked after a failed one are cancelled.
+                    res if self.write_error.is_none() => {
+                        self.write_error = Some(Errno::from_i32(-res))
+                    }
+                    _ => {}
+                }
+            } else {
+                self.reads_in_flight -= 1;
+                self.completed
+                    .push((cqe.user_data() as usize, cqe.result()));
+            }
+        }
+    }
+
+    // Hands out the completed reads as a batch of packets.
+    fn take_packets(&mut self) -> Result<Packets<'_>> {
+        // The buffers are read into again by the next call, after the packets are dropped.
+        self.idle
+            .extend(self.completed.iter().map(|&(index, _)| index));
+
+        if let Some(&(_, res)) = self.completed.iter().find(|&&(_, res)| res < 0) {
+            // The packets read along with the failed one are dropped.
+            self.completed.clear();
+
+            return Err(Errno::from_i32(-res)).context("IORING_OP_READ_FIXED", &self.device.name);
+        }
+
+        let buffers = self.buffers.as_ptr();
+        let packets = self
+            .completed
+            .drain(..)
+            .map(|(index, res)| unsafe {
+                slice::from_raw_parts(buffers.add(index * self.buf_size), res as usize)
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Packets(packets.into_iter()))
+    }
+
+    // Doesn't create a reference to the buffers, which the kernel might be writing into.
+    fn buffer(&mut self, index: usize) -> *mut u8 {
+        unsafe { self.buffers.as_mut_ptr().add(index * self.buf_size) }
+    }
+
+    // Cancels the reads in flight and waits for all requests to complete, since the kernel
+    // might access the buffers until then.
+    fn cancel(&mut self) -> Result<()> {
+        self.wait_writes()?;
+
+        for index in 0..self.entries {
+            let entry = opcode::AsyncCancel::new(index as u64)
+                .build()
+                .user_data(CANCEL_TAG | index as u64);
+
+            unsafe { self.ring.push(&entry) }?;
+        }
+
+        self.ring.enter(0)?;
+        self.reap();
+
+        while self.reads_in_flight > 0 {
+            self.ring.enter(1)?;
+            self.reap();
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for IoUring {
+    fn drop(&mut self) {
+        // Leaking the buffers is the only safe option if the requests couldn't be cancelled.
+        if self.cancel().is_err() {
+            mem::forget(mem::take(&mut self.buffers));
+        }
+    }
+}
+
+/// A batch of packets read by [`IoUring::recv_batch`], in the order they were read.
+#[derive(Debug)]
+pub struct Packets<'a>(std::vec::IntoIter<&'a [u8]>);
+
+impl<'a> Iterator for Packets<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Packets<'_> {}
+
+/// The asynchronous version of [`IoUring`].
+///
+/// The completions are waited for using an eventfd registered with the ring, so the device
+/// itself stays blocking.
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[derive(Debug)]
+pub struct AsyncIoUring {
+    uring: IoUring,
+    eventfd: tokio::io::unix::AsyncFd<OwnedFd>,
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncIoUring {
+    /// Creates an io_uring instance for reading and writing the packets of `device`.
+    ///
+    /// Takes the same arguments as [`IoUring::new`]. The device must be blocking, since the
+    /// ring would otherwise fail the reads with `EAGAIN` instead of waiting for packets.
+    pub fn new(device: &Device, entries: u32, buf_size: usize) -> Result<Self> {
+        let uring = IoUring::new(device, entries, buf_size)?;
+
+        let fd = unsafe { nix::libc::eventfd(0, nix::libc::EFD_NONBLOCK | nix::libc::EFD_CLOEXEC) };
+        let fd = Errno::result(fd).op_context("eventfd")?;
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        uring
+            .ring
+            .0
+            .submitter()
+            .register_eventfd(fd.as_raw_fd())
+            .op_context("io_uring_register")?;
+
+        Ok(AsyncIoUring {
+            uring,
+            eventfd: tokio::io::unix::AsyncFd::new(fd)?,
+        })
+    }
+
+    /// Returns the device whose packets are read and written.
+    pub fn device(&self) -> &Device {
+        self.uring.device()
+    }
+
+    /// Asyncronously waits until at least one packet is read from the device, then returns
+    /// the packets read so far, in order.
+    pub async fn recv_batch(&mut self) -> Result<Packets<'_>> {
+        loop {
+            self.uring.arm_reads()?;
+            self.uring.ring.enter(0)?;
+            self.uring.reap();
+
+            if !self.uring.completed.is_empty() {
+                return self.uring.take_packets();
+            }
+
+            self.wait().await?;
+        }
+    }
+
+    /// Asyncronously writes `packets` to the device, in order.
+    ///
+    /// Returns the same as [`IoUring::send_batch`].
+    pub async fn send_batch<P: AsRef<[u8]>>(&mut self, packets: &[P]) -> Result<usize> {
+        let mut sent = 0;
+
+        for chunk in packets.chunks(self.uring.entries.max(1)) {
+            self.wait_writes().await?;
+            self.uring.push_writes(chunk)?;
+            self.wait_writes().await?;
+
+            if !self.uring.finish_writes(chunk.len(), &mut sent)? {
+                break;
+            }
+        }
+
+        Ok(sent)
+    }
+
+    // Submits the queued requests and waits until all writes are completed.
+    async fn wait_writes(&mut self) -> Result<()> {
+        loop {
+            self.uring.ring.enter(0)?;
+            self.uring.reap();
+
+            if self.uring.writes_in_flight == 0 {
+                return Ok(());
+            }
+
+            self.wait().await?;
+        }
+    }
+
+    // Waits for the eventfd, which is signaled on every completion.
+    async fn wait(&self) -> Result<()> {
+        let mut counter = [0u8; 8];
+
+        loop {
+            let mut guard = self.eventfd.readable().await?;
+
+            match guard.try_io(|fd| {
+                nix::unistd::read(fd.as_raw_fd(), &mut counter).map_err(std::io::Error::from)
+            }) {
+                Ok(result) => return Ok(result.map(|_| ())?),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+}
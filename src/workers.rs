@@ -0,0 +1,222 @@
+//! A thread-per-queue runtime for multiqueue devices.
+
+use std::ops;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nix::poll::PollFlags;
+use nix::sched::{sched_getaffinity, sched_setaffinity, CpuSet};
+use nix::unistd::Pid;
+
+use crate::error::{Context, Error, Result};
+use crate::multiq::MQDevice;
+use crate::PacketBuf;
+
+// How often the workers check whether they should shut down.
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Options of the workers spawned by [`Workers::spawn`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct WorkerOptions {
+    /// CPUs the workers are pinned to. The worker of the `i`th queue is pinned to
+    /// `cpus[i % cpus.len()]`. The workers are not pinned if it's empty.
+    ///
+    /// The CPUs must be allowed for the process, see [`allowed_cpus`].
+    pub cpus: Vec<usize>,
+
+    /// Detaches a queue and stops its worker once no packet was received for this long,
+    /// so the kernel steers the flows of the queue to the remaining ones.
+    pub idle_timeout: Option<Duration>,
+}
+
+/// Worker threads, each reading the packets of one queue of a multiqueue device and
+/// passing them to a handler.
+///
+/// The worker of a queue stops when its handler or reading from its queue fails, without
+/// affecting the other workers. The packets are read into a buffer sized from
+/// [`Device::buffer_size`](crate::Device::buffer_size) when the worker starts, so a larger
+/// packet, e.g. after the MTU was increased, stops the worker with
+/// [`Error::PacketTruncated`] instead of reaching the handler cut short.
+#[derive(Debug)]
+pub struct Workers<D> {
+    shutdown: Arc<AtomicBool>,
+    threads: Vec<thread::JoinHandle<std::result::Result<D, (D, Error)>>>,
+}
+
+impl<D> Workers<D>
+where
+    D: ops::Deref<Target = MQDevice> + Send + 'static,
+{
+    /// Spawns a worker thread per queue in `queues`, which calls `handler` for each packet
+    /// read from the queue.
+    ///
+    /// The handler gets the index of the queue in `queues`, the queue itself, e.g. for
+    /// writing a reply, and the packet.
+    ///
+    /// # Returns
+    /// * `Ok`: If all the workers were spawned.
+    /// * `Err(Error::CpuNotAllowed)`: If one of `options.cpus` is not allowed for the process.
+    /// * `Err`: If spawning a thread failed. The already spawned workers are shut down.
+    pub fn spawn<F>(queues: Vec<D>, options: WorkerOptions, handler: F) -> Result<Self>
+    where
+        F: Fn(usize, &MQDevice, &mut [u8]) -> Result<()> + Send + Sync + 'static,
+    {
+        if !options.cpus.is_empty() {
+            let allowed = allowed_cpus()?;
+
+            if let Some(&cpu) = options.cpus.iter().find(|cpu| !allowed.contains(cpu)) {
+                return Err(Error::CpuNotAllowed(cpu));
+            }
+        }
+
+        let mut workers = Workers {
+            shutdown: Arc::new(AtomicBool::new(false)),
+            threads: Vec::with_capacity(queues.len()),
+        };
+
+        let options = Arc::new(options);
+        let handler = Arc::new(handler);
+
+        for (index, queue) in queues.into_iter().enumerate() {
+            let worker = Worker {
+                index,
+                shutdown: workers.shutdown.clone(),
+                options: options.clone(),
+            };
+            let handler = handler.clone();
+
+            let spawned = thread::Builder::new()
+                .name(format!("{}-queue-{}", queue.name, index))
+                .spawn(move || {
+                    // Catching the panics of the handler keeps the queue, so it's returned
+                    // along with the error.
+                    let result =
+                        panic::catch_unwind(AssertUnwindSafe(|| worker.run(&queue, &*handler)));
+
+                    match result {
+                        Ok(Ok(())) => Ok(queue),
+                        Ok(Err(err)) => Err((queue, err)),
+                        Err(_) => Err((queue, Error::WorkerPanicked(index))),
+                    }
+                });
+
+            match spawned {
+                Ok(thread) => workers.threads.push(thread),
+                Err(err) => {
+                    workers.shutdown();
+                    return Err(err.into());
+                }
+            }
+        }
+
+        Ok(workers)
+    }
+
+    /// Returns the number of workers.
+    pub fn len(&self) -> usize {
+        self.threads.len()
+    }
+
+    /// Returns whether there are no workers.
+    pub fn is_empty(&self) -> bool {
+        self.threads.is_empty()
+    }
+
+    /// Returns the number of workers which haven't stopped yet.
+    pub fn running(&self) -> usize {
+        self.threads
+            .iter()
+            .filter(|thread| !thread.is_finished())
+            .count()
+    }
+
+    /// Signals the workers to stop and waits for them.
+    ///
+    /// See [`join`](Self::join) for the returned results.
+    pub fn shutdown(self) -> Vec<std::result::Result<D, (D, Error)>> {
+        self.shutdown.store(true, Ordering::Relaxed);
+
+        self.join()
+    }
+
+    /// Waits for the workers to stop on their own, i.e. because of an error or being idle.
+    ///
+    /// # Returns
+    /// The result of each worker, in the order of the queues:
+    /// * `Ok`: Containing the queue, if the worker stopped normally.
+    /// * `Err((queue, Error::WorkerPanicked))`: If the handler panicked.
+    /// * `Err((queue, err))`: If the handler or reading from the queue failed.
+    pub fn join(self) -> Vec<std::result::Result<D, (D, Error)>> {
+        self.threads
+            .into_iter()
+            // The panics of the handlers are caught by the workers themselves.
+            .map(|thread| thread.join().unwrap())
+            .collect()
+    }
+}
+
+// State of a worker thread.
+struct Worker {
+    index: usize,
+    shutdown: Arc<AtomicBool>,
+    options: Arc<WorkerOptions>,
+}
+
+impl Worker {
+    fn run<D, F>(&self, queue: &D, handler: &F) -> Result<()>
+    where
+        D: ops::Deref<Target = MQDevice>,
+        F: Fn(usize, &MQDevice, &mut [u8]) -> Result<()>,
+    {
+        if !self.options.cpus.is_empty() {
+            let mut cpu_set = CpuSet::new();
+            cpu_set
+                .set(self.options.cpus[self.index % self.options.cpus.len()])
+                .op_context("sched_setaffinity")?;
+
+            sched_setaffinity(Pid::from_raw(0), &cpu_set).op_context("sched_setaffinity")?;
+        }
+
+        let mut buf = PacketBuf::new(queue.buffer_size()?);
+        let mut last_packet = Instant::now();
+
+        while !self.shutdown.load(Ordering::Relaxed) {
+            match queue.with_timeout(PollFlags::POLLIN, SHUTDOWN_POLL_INTERVAL, || {
+                queue.recv_buf(&mut buf)
+            }) {
+                Ok(_) if buf.is_truncated() => return Err(Error::PacketTruncated(buf.capacity())),
+                Ok(_) => {
+                    handler(self.index, queue, &mut buf)?;
+                    last_packet = Instant::now();
+                }
+                Err(Error::TimedOut) => {
+                    if let Some(idle_timeout) = self.options.idle_timeout {
+                        if last_packet.elapsed() >= idle_timeout {
+                            queue.detach()?;
+                            break;
+                        }
+                    }
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the CPUs the calling thread is allowed to run on, e.g. for building
+/// [`WorkerOptions::cpus`].
+///
+/// The allowed CPUs can be limited by a cgroup or by `taskset`, so they aren't necessarily
+/// `0..available_parallelism()`.
+pub fn allowed_cpus() -> Result<Vec<usize>> {
+    let cpu_set = sched_getaffinity(Pid::from_raw(0)).op_context("sched_getaffinity")?;
+
+    Ok((0..CpuSet::count())
+        .filter(|&cpu| cpu_set.is_set(cpu).unwrap_or(false))
+        .collect())
+}